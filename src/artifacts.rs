@@ -0,0 +1,58 @@
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+use exn::{Result, ResultExt};
+use zip::ZipArchive;
+
+use crate::configuration::ArtifactSettings;
+use crate::error::AppError;
+use crate::models::{Artifact, WorkflowRun};
+
+/// The directory an artifact is extracted to:
+/// `<directory>/<owner>/<repo>/<run number>-<attempt>/<artifact>`.
+pub fn destination(
+    settings: &ArtifactSettings,
+    workflow: &WorkflowRun,
+    artifact: &Artifact,
+) -> Option<PathBuf> {
+    let directory = match &settings.directory {
+        Some(d) => d.clone(),
+        None => default_directory()?,
+    };
+
+    Some(
+        directory
+            .join(sanitize(&workflow.owner))
+            .join(sanitize(&workflow.repo))
+            .join(format!("{}-{}", workflow.run_number, workflow.run_attempt))
+            .join(sanitize(&artifact.name)),
+    )
+}
+
+/// Extract the zip archive of an artifact into `directory`, overwriting the
+/// files of a previous download.
+///
+/// The entries escaping `directory`, e.g. with `..`, are rejected.
+pub fn extract(archive: &Path, directory: &Path) -> Result<(), AppError> {
+    let make_error = || AppError::from(format!("failed to extract {}", archive.display()));
+
+    let file = File::open(archive).or_raise(make_error)?;
+    let mut zip = ZipArchive::new(file).or_raise(make_error)?;
+
+    std::fs::create_dir_all(directory).or_raise(make_error)?;
+    zip.extract(directory).or_raise(make_error)
+}
+
+fn default_directory() -> Option<PathBuf> {
+    dirs::download_dir()
+        .or_else(dirs::home_dir)
+        .map(|d| d.join("gh-dashboard"))
+}
+
+/// The names come from the API, they must not be able to leave the directory.
+fn sanitize(name: &str) -> String {
+    match name {
+        "" | "." | ".." => "_".to_string(),
+        name => name.replace(['/', '\\'], "_"),
+    }
+}
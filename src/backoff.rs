@@ -0,0 +1,35 @@
+use std::time::Duration;
+
+/// An exponential backoff between retries.
+#[derive(Debug, Clone)]
+pub struct Backoff {
+    min: Duration,
+    max: Duration,
+    current: Option<Duration>,
+}
+
+impl Backoff {
+    pub const fn new(min: Duration, max: Duration) -> Self {
+        Self {
+            min,
+            max,
+            current: None,
+        }
+    }
+
+    /// The delay before the next retry, doubling on each call up to the
+    /// maximum.
+    pub fn next_delay(&mut self) -> Duration {
+        let delay = match self.current {
+            Some(current) => (current * 2).min(self.max),
+            None => self.min,
+        };
+        self.current = Some(delay);
+
+        delay
+    }
+
+    pub fn reset(&mut self) {
+        self.current = None;
+    }
+}
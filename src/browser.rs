@@ -0,0 +1,71 @@
+use std::process::{Command, Stdio};
+use std::thread;
+
+use exn::{Result, ResultExt, bail};
+use log::debug;
+
+use crate::configuration::{BrowserCommand, BrowserSettings};
+use crate::error::AppError;
+use crate::session;
+
+/// Opens the URLs in a browser, with the configured command or the default
+/// browser of the desktop.
+#[derive(Debug, Clone, Default)]
+pub struct Browser {
+    command: Option<Vec<String>>,
+}
+
+impl Browser {
+    pub fn new(settings: &BrowserSettings) -> Self {
+        let command = settings
+            .command
+            .as_ref()
+            .map(BrowserCommand::args)
+            .filter(|c| !c.is_empty());
+
+        Self { command }
+    }
+
+    pub fn open(&self, url: &str) -> Result<(), AppError> {
+        let make_error = || AppError::from(format!("unable to open {}", url));
+
+        if let Some(command) = &self.command {
+            return spawn(command, url).or_raise(make_error);
+        }
+
+        // Without a display, the default browser is a text browser taking
+        // over the terminal, or nothing at all.
+        if session::is_remote() || !session::has_display() {
+            bail!(AppError::from(format!("no browser to open {}", url)));
+        }
+
+        open::that_detached(url).or_raise(make_error)
+    }
+}
+
+/// Run the command with the URL in place of `{url}`, or after the arguments.
+fn spawn(command: &[String], url: &str) -> std::io::Result<()> {
+    let mut args: Vec<_> = command.iter().map(|a| a.replace("{url}", url)).collect();
+    if !command.iter().any(|a| a.contains("{url}")) {
+        args.push(url.to_string());
+    }
+
+    debug!("Opening {} with {:?}", url, args);
+
+    let Some((program, args)) = args.split_first() else {
+        return Err(std::io::Error::other("empty command"));
+    };
+
+    // The output would be drawn over the dashboard.
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+
+    // Reap the process once the browser is started.
+    thread::spawn(move || child.wait());
+
+    Ok(())
+}
@@ -0,0 +1,70 @@
+use std::io::{Write, stdout};
+use std::sync::{Mutex, OnceLock};
+
+use arboard::Clipboard;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use exn::{Result, ResultExt};
+use log::{debug, warn};
+
+use crate::error::AppError;
+use crate::session;
+
+/// Copy `text` to the clipboard.
+///
+/// The text is sent to the terminal with the OSC 52 escape sequence, which
+/// works over SSH as long as the terminal supports it. Outside of SSH, it is
+/// also copied to the native clipboard, for the terminals that ignore the
+/// sequence.
+pub fn copy(text: &str) -> Result<(), AppError> {
+    let osc52 = copy_osc52(text);
+
+    if session::is_remote() {
+        return osc52;
+    }
+
+    match (osc52, copy_native(text)) {
+        (Err(err), Err(native_err)) => {
+            warn!("Failed to copy to the native clipboard: {:?}", native_err);
+            Err(err)
+        }
+        (_, Err(native_err)) => {
+            debug!("Failed to copy to the native clipboard: {:?}", native_err);
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+fn copy_osc52(text: &str) -> Result<(), AppError> {
+    let make_error = || AppError::from("failed to copy to the clipboard");
+
+    // A single write, so that the sequence isn't interleaved with a frame.
+    let sequence = format!("\x1b]52;c;{}\x07", STANDARD.encode(text));
+
+    let mut out = stdout().lock();
+    out.write_all(sequence.as_bytes()).or_raise(make_error)?;
+    out.flush().or_raise(make_error)
+}
+
+fn copy_native(text: &str) -> Result<(), AppError> {
+    let make_error = || AppError::from("failed to copy to the native clipboard");
+
+    // On X11 and Wayland the copied text is served by the clipboard, so it is
+    // kept for the lifetime of the app rather than dropped after each copy.
+    static CLIPBOARD: OnceLock<Option<Mutex<Clipboard>>> = OnceLock::new();
+
+    let clipboard = CLIPBOARD.get_or_init(|| match Clipboard::new() {
+        Ok(clipboard) => Some(Mutex::new(clipboard)),
+        Err(err) => {
+            debug!("No native clipboard: {}", err);
+            None
+        }
+    });
+    let Some(clipboard) = clipboard else {
+        return Err(make_error().into());
+    };
+
+    let mut clipboard = clipboard.lock().unwrap_or_else(|e| e.into_inner());
+    clipboard.set_text(text).or_raise(make_error)
+}
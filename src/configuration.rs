@@ -1,15 +1,21 @@
 use std::collections::HashMap;
 use std::env;
+use std::path::PathBuf;
 use std::process::Command;
+use std::str::FromStr;
 
+use chrono::FixedOffset;
+use chrono::format::{Item, StrftimeItems};
 use config::{Value, ValueKind};
 use exn::{Result, ResultExt, bail};
 use gh_dashboard::Error;
 use log::debug;
+use ratatui::style::{Color, Modifier, Style};
 use secrecy::SecretString;
 use serde::Deserialize;
 
-use crate::models::Repository;
+use crate::keymap::{Action, KeySequence, Keymap};
+use crate::models::RepositorySource;
 
 #[derive(Debug, Error)]
 pub struct AuthError(String);
@@ -17,7 +23,26 @@ pub struct AuthError(String);
 #[derive(Debug, Clone, Deserialize)]
 pub struct Settings {
     pub host: String,
-    pub repos: Vec<Repository>,
+    pub repos: Vec<RepositorySource>,
+    /// The number of completed runs used to compute the workflow statistics.
+    pub stats_runs: u8,
+    #[serde(default)]
+    pub history: HistorySettings,
+    #[serde(default)]
+    pub time: TimeSettings,
+    #[serde(default)]
+    pub refresh: RefreshSettings,
+    /// The keys of each action, replacing the default ones.
+    #[serde(default)]
+    pub keys: HashMap<Action, Vec<KeySequence>>,
+    #[serde(default)]
+    pub theme: ThemeSettings,
+    #[serde(default)]
+    pub layout: LayoutSettings,
+    #[serde(default)]
+    pub browser: BrowserSettings,
+    #[serde(default)]
+    pub artifacts: ArtifactSettings,
     auth_token: Option<SecretString>,
 }
 
@@ -63,6 +88,36 @@ impl Default for Settings {
             host: "github.com".to_string(),
             auth_token: None,
             repos: vec![],
+            stats_runs: 20,
+            history: HistorySettings::default(),
+            time: TimeSettings::default(),
+            refresh: RefreshSettings::default(),
+            keys: HashMap::new(),
+            theme: ThemeSettings::default(),
+            layout: LayoutSettings::default(),
+            browser: BrowserSettings::default(),
+            artifacts: ArtifactSettings::default(),
+        }
+    }
+}
+
+/// The local history store of the observed runs.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct HistorySettings {
+    pub enabled: bool,
+    /// Defaults to `<data-dir>/gh-dashboard/history.sqlite`.
+    pub path: Option<PathBuf>,
+    /// The number of days the runs are kept for.
+    pub retention_days: u32,
+}
+
+impl Default for HistorySettings {
+    fn default() -> Self {
+        HistorySettings {
+            enabled: true,
+            path: None,
+            retention_days: 30,
         }
     }
 }
@@ -80,6 +135,239 @@ impl From<Settings> for ValueKind {
     }
 }
 
+/// How often the data is refreshed. All the intervals are in seconds.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct RefreshSettings {
+    /// The default interval, can be overridden per repository.
+    pub interval: u64,
+    /// Poll faster while runs are in progress, and slower when everything is
+    /// complete or the terminal is unfocused.
+    pub adaptive: bool,
+    /// The interval while a run is queued or in progress.
+    pub active_interval: u64,
+    /// The interval when every run is complete, or the terminal is unfocused.
+    pub idle_interval: u64,
+}
+
+impl Default for RefreshSettings {
+    fn default() -> Self {
+        RefreshSettings {
+            interval: 60,
+            adaptive: true,
+            active_interval: 10,
+            idle_interval: 300,
+        }
+    }
+}
+
+/// How the timestamps are displayed.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct TimeSettings {
+    /// Show relative times, e.g. `3m ago`, instead of timestamps.
+    pub relative: bool,
+    pub timezone: TimeZoneSetting,
+    /// A `strftime` format, used for the timestamps.
+    pub format: String,
+}
+
+impl Default for TimeSettings {
+    fn default() -> Self {
+        TimeSettings {
+            relative: false,
+            timezone: TimeZoneSetting::Local,
+            format: "%Y-%m-%d %H:%M:%S".to_string(),
+        }
+    }
+}
+
+/// The time zone of the displayed timestamps: `local`, `utc`, or a fixed
+/// offset such as `+02:00`.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(try_from = "String")]
+pub enum TimeZoneSetting {
+    Local,
+    Utc,
+    Fixed(FixedOffset),
+}
+
+impl TryFrom<String> for TimeZoneSetting {
+    type Error = String;
+
+    fn try_from(value: String) -> std::result::Result<Self, Self::Error> {
+        match value.to_lowercase().as_str() {
+            "local" => Ok(Self::Local),
+            "utc" => Ok(Self::Utc),
+            v => FixedOffset::from_str(v)
+                .map(Self::Fixed)
+                .map_err(|e| format!("invalid timezone {}: {}", value, e)),
+        }
+    }
+}
+
+/// How the runs are laid out.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct LayoutSettings {
+    pub mode: LayoutMode,
+    pub details: DetailsPlacement,
+}
+
+/// Where the details of a run are shown.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DetailsPlacement {
+    /// Over the runs, when asked for.
+    #[default]
+    Popup,
+    /// Next to the runs, following the selection.
+    Right,
+    /// Below the runs, following the selection.
+    Bottom,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LayoutMode {
+    /// A table, or cards when the terminal is tall and narrow.
+    #[default]
+    Auto,
+    Table,
+    /// A card per run, for tall and narrow panes.
+    Cards,
+    /// Large tiles of the failing and in-progress runs, for TV displays.
+    Wallboard,
+}
+
+/// How the runs and the jobs are opened.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct BrowserSettings {
+    /// The command opening the URLs, e.g. `firefox --new-tab`. The URL replaces
+    /// `{url}`, or is appended to the arguments. Defaults to the browser of the
+    /// desktop.
+    pub command: Option<BrowserCommand>,
+}
+
+/// A command line split on whitespace, or the list of its arguments when they
+/// contain spaces, e.g. `["/opt/My Browser/browser", "--new-tab"]`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum BrowserCommand {
+    Line(String),
+    Args(Vec<String>),
+}
+
+impl BrowserCommand {
+    pub fn args(&self) -> Vec<String> {
+        match self {
+            BrowserCommand::Line(line) => line.split_whitespace().map(String::from).collect(),
+            BrowserCommand::Args(args) => args.clone(),
+        }
+    }
+}
+
+/// Where the artifacts of the runs are downloaded.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ArtifactSettings {
+    /// Defaults to `<download-dir>/gh-dashboard`.
+    pub directory: Option<PathBuf>,
+}
+
+/// The colours and symbols of the dashboard.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ThemeSettings {
+    /// A built-in theme: `dark`, `light`, `high-contrast` or `colour-blind`,
+    /// or one of the `custom` themes.
+    pub name: String,
+    pub symbols: SymbolSet,
+    pub custom: HashMap<String, CustomTheme>,
+}
+
+impl Default for ThemeSettings {
+    fn default() -> Self {
+        ThemeSettings {
+            name: "dark".to_string(),
+            symbols: SymbolSet::Emoji,
+            custom: HashMap::new(),
+        }
+    }
+}
+
+/// The symbols of the statuses, `ascii` for the terminals that can't render
+/// the emoji.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SymbolSet {
+    #[default]
+    Emoji,
+    Ascii,
+}
+
+/// A user-defined theme, the styles that aren't set are the ones of the `base`
+/// theme.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct CustomTheme {
+    /// Defaults to `dark`.
+    pub base: Option<String>,
+    pub title: Option<StyleSetting>,
+    pub header: Option<StyleSetting>,
+    pub highlight: Option<StyleSetting>,
+    pub success: Option<StyleSetting>,
+    pub failure: Option<StyleSetting>,
+    pub cancelled: Option<StyleSetting>,
+    pub skipped: Option<StyleSetting>,
+    pub pending: Option<StyleSetting>,
+    pub running: Option<StyleSetting>,
+    pub neutral: Option<StyleSetting>,
+}
+
+/// A style such as `bold yellow on blue`: modifiers, a foreground colour, and
+/// a background colour after `on`.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(try_from = "String")]
+pub struct StyleSetting(pub Style);
+
+impl TryFrom<String> for StyleSetting {
+    type Error = String;
+
+    fn try_from(value: String) -> std::result::Result<Self, Self::Error> {
+        let mut style = Style::new();
+        let mut words = value.split_whitespace();
+
+        while let Some(word) = words.next() {
+            style = match word.to_lowercase().as_str() {
+                "bold" => style.add_modifier(Modifier::BOLD),
+                "dim" => style.add_modifier(Modifier::DIM),
+                "italic" => style.add_modifier(Modifier::ITALIC),
+                "underlined" => style.add_modifier(Modifier::UNDERLINED),
+                "reversed" => style.add_modifier(Modifier::REVERSED),
+                "on" => {
+                    let color = words
+                        .next()
+                        .ok_or_else(|| format!("missing background colour in {}", value))?;
+                    style.bg(parse_color(color)?)
+                }
+                color => style.fg(parse_color(color)?),
+            };
+        }
+
+        Ok(Self(style))
+    }
+}
+
+fn parse_color(value: &str) -> std::result::Result<Color, String> {
+    Color::from_str(value).map_err(|e| format!("invalid colour {}: {}", value, e))
+}
+
+fn is_valid_time_format(format: &str) -> bool {
+    StrftimeItems::new(format).all(|item| !matches!(item, Item::Error))
+}
+
 #[derive(Debug, Error)]
 pub struct ConfigError(String);
 
@@ -118,8 +406,45 @@ pub fn get_configuration() -> Result<Settings, ConfigError> {
         .add_source(config::Environment::with_prefix("GH_DASHBOARD"))
         .set_default("host", default.host)
         .or_raise(make_err)?
+        .set_default("stats_runs", default.stats_runs)
+        .or_raise(make_err)?
         .build()
         .or_raise(make_err)?;
 
-    settings.try_deserialize::<Settings>().or_raise(make_err)
+    let settings = settings.try_deserialize::<Settings>().or_raise(make_err)?;
+
+    // Formatting a timestamp with an invalid format panics while rendering.
+    if !is_valid_time_format(&settings.time.format) {
+        bail!(ConfigError::from(format!(
+            "invalid time format: {}",
+            settings.time.format
+        )));
+    }
+
+    // The keys of an action replace its defaults, but the other actions keep
+    // theirs, e.g. binding `g` shadows the default `g g`.
+    let conflicts = Keymap::new(&settings.keys).conflicts();
+    if !conflicts.is_empty() {
+        bail!(ConfigError::from(format!(
+            "conflicting keys: {}",
+            conflicts.join(", ")
+        )));
+    }
+
+    Ok(settings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_time_formats() {
+        assert!(is_valid_time_format(&TimeSettings::default().format));
+        assert!(is_valid_time_format("%d/%m %H:%M"));
+        assert!(is_valid_time_format("at %R, %a"));
+
+        assert!(!is_valid_time_format("%Y-%m-%d %Q"));
+        assert!(!is_valid_time_format("%H:%M %"));
+    }
 }
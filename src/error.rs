@@ -13,3 +13,6 @@ impl AppError {
 
 #[derive(Debug, Error)]
 pub struct ServiceError(String);
+
+#[derive(Debug, Error)]
+pub struct StorageError(String);
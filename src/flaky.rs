@@ -0,0 +1,228 @@
+use std::collections::HashMap;
+
+use octocrab::models::RunId;
+
+use crate::models::{WorkflowJob, WorkflowJobConclusion};
+
+/// A job that failed and then passed on the same commit.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlakyJob {
+    pub workflow: String,
+    pub name: String,
+    /// The number of times the job failed, and passed on its next execution.
+    pub flakes: usize,
+    /// The number of completed executions of the job.
+    pub executions: usize,
+}
+
+impl FlakyJob {
+    pub fn rate(&self) -> f64 {
+        self.flakes as f64 / self.executions.max(1) as f64
+    }
+}
+
+/// What the executions of a job are compared on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Revision<'a> {
+    Commit(&'a str),
+    /// The commit of the jobs observed before it was recorded is unknown, only
+    /// the attempts of their run are compared.
+    Run(RunId),
+}
+
+impl<'a> Revision<'a> {
+    fn of(job: &'a WorkflowJob) -> Self {
+        match job.head_sha.is_empty() {
+            true => Self::Run(job.run_id),
+            false => Self::Commit(&job.head_sha),
+        }
+    }
+}
+
+/// Find the flaky jobs in the job history of a repository, the flakiest
+/// first.
+///
+/// `jobs` are the jobs with the name of their workflow, ordered by the start of
+/// their run and then by attempt. A job is flaky when it failed on a commit and
+/// passed on its next execution on the same commit, whether the run was re-run
+/// or the commit was built again by another run.
+pub fn detect(jobs: &[(String, WorkflowJob)]) -> Vec<FlakyJob> {
+    let mut executions: HashMap<(&str, &str), HashMap<Revision, Vec<&WorkflowJob>>> =
+        HashMap::new();
+    for (workflow, job) in jobs {
+        if is_failure(job) || is_success(job) {
+            executions
+                .entry((workflow.as_str(), job.name.as_str()))
+                .or_default()
+                .entry(Revision::of(job))
+                .or_default()
+                .push(job);
+        }
+    }
+
+    let mut flaky: Vec<_> = executions
+        .into_iter()
+        .map(|((workflow, name), revisions)| {
+            let executions = revisions.values().map(Vec::len).sum();
+            let flakes = revisions
+                .into_values()
+                .map(|mut jobs| {
+                    sort_attempts(&mut jobs);
+                    jobs.windows(2)
+                        .filter(|pair| is_failure(pair[0]) && is_success(pair[1]))
+                        .count()
+                })
+                .sum();
+
+            FlakyJob {
+                workflow: workflow.to_string(),
+                name: name.to_string(),
+                flakes,
+                executions,
+            }
+        })
+        .filter(|j| j.flakes > 0)
+        .collect();
+
+    flaky.sort_by(|a, b| {
+        b.flakes
+            .cmp(&a.flakes)
+            .then(b.rate().total_cmp(&a.rate()))
+            .then_with(|| (&a.workflow, &a.name).cmp(&(&b.workflow, &b.name)))
+    });
+
+    flaky
+}
+
+/// Keep the runs in their order, and the attempts of a run in theirs.
+fn sort_attempts(jobs: &mut [&WorkflowJob]) {
+    let mut runs: HashMap<RunId, usize> = HashMap::new();
+    for job in jobs.iter() {
+        let next = runs.len();
+        runs.entry(job.run_id).or_insert(next);
+    }
+
+    jobs.sort_by_key(|j| (runs[&j.run_id], j.run_attempt));
+}
+
+fn is_failure(job: &WorkflowJob) -> bool {
+    matches!(
+        job.conclusion,
+        WorkflowJobConclusion::Failure | WorkflowJobConclusion::TimedOut
+    )
+}
+
+fn is_success(job: &WorkflowJob) -> bool {
+    matches!(job.conclusion, WorkflowJobConclusion::Success)
+}
+
+#[cfg(test)]
+mod tests {
+    use fake::{Fake, Faker};
+
+    use super::*;
+
+    fn job(
+        name: &str,
+        head_sha: &str,
+        run_id: u64,
+        run_attempt: u64,
+        conclusion: WorkflowJobConclusion,
+    ) -> (String, WorkflowJob) {
+        let job = WorkflowJob {
+            name: name.to_string(),
+            head_sha: head_sha.to_string(),
+            run_id: run_id.into(),
+            run_attempt,
+            conclusion,
+            ..Faker.fake()
+        };
+
+        ("CI".to_string(), job)
+    }
+
+    fn flaky_job(name: &str, flakes: usize, executions: usize) -> FlakyJob {
+        FlakyJob {
+            workflow: "CI".to_string(),
+            name: name.to_string(),
+            flakes,
+            executions,
+        }
+    }
+
+    #[test]
+    fn detect_flaky_jobs_retried_in_the_same_run() {
+        use WorkflowJobConclusion::*;
+
+        let jobs = [
+            job("test", "a", 1, 1, Failure),
+            job("test", "a", 1, 2, Success),
+            // A timeout counts as a failure.
+            job("test", "b", 2, 1, TimedOut),
+            job("test", "b", 2, 2, Success),
+            // Passed, then failed: a broken commit rather than a flake.
+            job("test", "c", 3, 1, Success),
+            job("test", "c", 3, 2, Failure),
+        ];
+
+        assert_eq!(detect(&jobs), vec![flaky_job("test", 2, 6)]);
+    }
+
+    #[test]
+    fn detect_flaky_jobs_in_separate_runs_of_the_same_commit() {
+        use WorkflowJobConclusion::*;
+
+        let jobs = [
+            job("lint", "a", 1, 1, Failure),
+            job("build", "a", 1, 1, Success),
+            job("lint", "b", 2, 1, Failure),
+            // Cancelled executions are neither failures nor successes.
+            job("lint", "a", 3, 1, Cancelled),
+            job("lint", "a", 4, 1, Success),
+            // Another commit may have fixed the job.
+            job("lint", "c", 5, 1, Success),
+        ];
+
+        assert_eq!(detect(&jobs), vec![flaky_job("lint", 1, 4)]);
+    }
+
+    #[test]
+    fn detect_flaky_jobs_of_unknown_commits_in_their_run_only() {
+        use WorkflowJobConclusion::*;
+
+        let jobs = [
+            job("test", "", 1, 1, Failure),
+            job("test", "", 2, 1, Success),
+            job("test", "", 3, 2, Success),
+            job("test", "", 3, 1, Failure),
+        ];
+
+        assert_eq!(detect(&jobs), vec![flaky_job("test", 1, 4)]);
+    }
+
+    #[test]
+    fn rank_the_flakiest_jobs_first() {
+        use WorkflowJobConclusion::*;
+
+        let jobs = [
+            job("test", "a", 1, 1, Failure),
+            job("test", "a", 1, 2, Success),
+            job("lint", "a", 1, 1, Failure),
+            job("lint", "a", 1, 2, Success),
+            job("lint", "b", 2, 1, Success),
+            job("build", "a", 1, 1, Failure),
+            job("build", "a", 1, 2, Success),
+            job("build", "b", 2, 1, Failure),
+            job("build", "b", 2, 2, Success),
+        ];
+
+        assert_eq!(
+            detect(&jobs),
+            vec![
+                flaky_job("build", 2, 4),
+                flaky_job("test", 1, 2),
+                flaky_job("lint", 1, 3),
+            ]
+        );
+    }
+}
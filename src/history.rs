@@ -0,0 +1,619 @@
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use chrono::{DateTime, Utc};
+use exn::{Result, ResultExt};
+use log::{debug, info};
+use octocrab::models::{JobId, RunId, WorkflowId};
+use rusqlite::{Connection, OptionalExtension, Row, params};
+
+use crate::configuration::HistorySettings;
+use crate::error::StorageError;
+use crate::models::{
+    PullRequest,
+    WorkflowJob,
+    WorkflowJobConclusion,
+    WorkflowJobStatus,
+    WorkflowRun,
+    WorkflowRunConclusion,
+    WorkflowRunStatus,
+};
+
+/// The schema migrations, in order. The index of a migration + 1 is the schema
+/// version it migrates to, and is tracked in the `user_version` pragma.
+const MIGRATIONS: &[&str] = &[
+    // 1: initial schema
+    "
+    CREATE TABLE workflow_runs (
+        id INTEGER PRIMARY KEY,
+        workflow_id INTEGER NOT NULL,
+        owner TEXT NOT NULL,
+        repo TEXT NOT NULL,
+        branch TEXT NOT NULL,
+        name TEXT NOT NULL,
+        commit_message TEXT NOT NULL,
+        start_time TEXT NOT NULL,
+        run_started_at TEXT NOT NULL,
+        updated_at TEXT NOT NULL,
+        status TEXT NOT NULL,
+        conclusion TEXT,
+        html_url TEXT NOT NULL,
+        observed_at TEXT NOT NULL
+    );
+    CREATE INDEX workflow_runs_start_time ON workflow_runs (start_time);
+    CREATE INDEX workflow_runs_workflow ON workflow_runs (owner, repo, workflow_id);
+
+    CREATE TABLE workflow_jobs (
+        id INTEGER PRIMARY KEY,
+        run_id INTEGER NOT NULL,
+        name TEXT NOT NULL,
+        created_at TEXT NOT NULL,
+        started_at TEXT NOT NULL,
+        completed_at TEXT,
+        status TEXT NOT NULL,
+        conclusion TEXT NOT NULL,
+        html_url TEXT NOT NULL,
+        observed_at TEXT NOT NULL
+    );
+    CREATE INDEX workflow_jobs_run_id ON workflow_jobs (run_id);
+
+    -- The runs displayed after the last successful refresh, in display order.
+    CREATE TABLE latest_snapshot (
+        position INTEGER PRIMARY KEY,
+        run_id INTEGER NOT NULL
+    );
+    ",
+    // 2: the commit of the runs, unknown for the runs observed before
+    "ALTER TABLE workflow_runs ADD COLUMN head_sha TEXT NOT NULL DEFAULT '';",
+    // 3: the context of the runs
+    "
+    ALTER TABLE workflow_runs ADD COLUMN commit_author TEXT NOT NULL DEFAULT '';
+    ALTER TABLE workflow_runs ADD COLUMN event TEXT NOT NULL DEFAULT '';
+    ALTER TABLE workflow_runs ADD COLUMN actor TEXT NOT NULL DEFAULT '';
+    ALTER TABLE workflow_runs ADD COLUMN triggering_actor TEXT NOT NULL DEFAULT '';
+    ALTER TABLE workflow_runs ADD COLUMN run_number INTEGER NOT NULL DEFAULT 0;
+    ALTER TABLE workflow_runs ADD COLUMN run_attempt INTEGER NOT NULL DEFAULT 1;
+    ALTER TABLE workflow_runs ADD COLUMN pr_number INTEGER;
+    ALTER TABLE workflow_runs ADD COLUMN pr_title TEXT;
+    ",
+    // 4: the attempts of the runs
+    "
+    ALTER TABLE workflow_jobs ADD COLUMN run_attempt INTEGER NOT NULL DEFAULT 1;
+    ALTER TABLE workflow_jobs ADD COLUMN head_sha TEXT NOT NULL DEFAULT '';
+    ",
+    // 5: the check suite of the runs
+    "ALTER TABLE workflow_runs ADD COLUMN check_suite_id INTEGER;",
+    // 6: the runner labels of the jobs, comma-separated
+    "ALTER TABLE workflow_jobs ADD COLUMN labels TEXT NOT NULL DEFAULT '';",
+];
+
+const RUN_COLUMNS: &str = "id, workflow_id, owner, repo, branch, name, commit_message, \
+                           start_time, run_started_at, updated_at, status, conclusion, html_url, \
+                           head_sha, commit_author, event, actor, triggering_actor, run_number, \
+                           run_attempt, pr_number, pr_title, check_suite_id";
+
+const JOB_COLUMNS: &str = "id, run_id, name, created_at, started_at, completed_at, status, \
+                           conclusion, html_url, run_attempt, head_sha, labels";
+
+/// A local store of the observed workflow runs and jobs.
+///
+/// The runs and jobs are upserted each time they are fetched, so the store
+/// keeps their last known state, and runs older than the retention period are
+/// pruned.
+#[derive(Debug)]
+pub struct HistoryStore {
+    conn: Mutex<Connection>,
+    retention_days: u32,
+}
+
+impl HistoryStore {
+    /// Open the store configured in the settings, or `None` if it's disabled.
+    pub fn from_settings(settings: &HistorySettings) -> Result<Option<Self>, StorageError> {
+        if !settings.enabled {
+            return Ok(None);
+        }
+
+        let path = match &settings.path {
+            Some(p) => p.clone(),
+            None => default_path()
+                .ok_or_else(|| StorageError::from("unable to find the data directory"))?,
+        };
+
+        Self::open(&path, settings.retention_days).map(Some)
+    }
+
+    pub fn open(path: &Path, retention_days: u32) -> Result<Self, StorageError> {
+        let make_error = || StorageError::from(format!("error opening {}", path.display()));
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).or_raise(make_error)?;
+        }
+
+        let mut conn = Connection::open(path).or_raise(make_error)?;
+        migrate(&mut conn)?;
+
+        info!("Using history store at {}", path.display());
+
+        let store = Self {
+            conn: Mutex::new(conn),
+            retention_days,
+        };
+        store.prune()?;
+
+        Ok(store)
+    }
+
+    /// Run a query or a write off the async workers: the SQLite calls block,
+    /// and wait for the connection while another call holds it.
+    pub async fn run_blocking<T: Send + 'static>(
+        self: &Arc<Self>,
+        call: impl FnOnce(&HistoryStore) -> Result<T, StorageError> + Send + 'static,
+    ) -> Result<T, StorageError> {
+        let history = self.clone();
+
+        tokio::task::spawn_blocking(move || call(&history))
+            .await
+            .or_raise(|| StorageError::from("error accessing the history"))?
+    }
+
+    /// The connection, even if a task panicked while using it: the
+    /// transaction it left open was rolled back when it was dropped.
+    fn conn(&self) -> MutexGuard<'_, Connection> {
+        self.conn.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Save the runs, and record them as the latest snapshot.
+    pub fn save_snapshot(&self, runs: &[WorkflowRun]) -> Result<(), StorageError> {
+        let make_error = || StorageError::from("error saving workflow runs");
+
+        let mut conn = self.conn();
+        let tx = conn.transaction().or_raise(make_error)?;
+
+        upsert_runs(&tx, runs).or_raise(make_error)?;
+
+        tx.execute("DELETE FROM latest_snapshot", [])
+            .or_raise(make_error)?;
+        {
+            let mut stmt = tx
+                .prepare_cached("INSERT INTO latest_snapshot (position, run_id) VALUES (?1, ?2)")
+                .or_raise(make_error)?;
+            for (position, run) in runs.iter().enumerate() {
+                stmt.execute(params![position as i64, to_sql_id(run.id.into_inner())])
+                    .or_raise(make_error)?;
+            }
+        }
+
+        tx.commit().or_raise(make_error)
+    }
+
+    /// Save runs that aren't part of the displayed list, e.g. the history of a
+    /// workflow.
+    pub fn save_runs(&self, runs: &[WorkflowRun]) -> Result<(), StorageError> {
+        let make_error = || StorageError::from("error saving workflow runs");
+
+        let mut conn = self.conn();
+        let tx = conn.transaction().or_raise(make_error)?;
+        upsert_runs(&tx, runs).or_raise(make_error)?;
+        tx.commit().or_raise(make_error)
+    }
+
+    pub fn save_jobs(&self, jobs: &[WorkflowJob]) -> Result<(), StorageError> {
+        let make_error = || StorageError::from("error saving workflow jobs");
+
+        let now = Utc::now();
+        let mut conn = self.conn();
+        let tx = conn.transaction().or_raise(make_error)?;
+        {
+            let mut stmt = tx
+                .prepare_cached(&format!(
+                    "INSERT OR REPLACE INTO workflow_jobs ({}, observed_at)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+                    JOB_COLUMNS
+                ))
+                .or_raise(make_error)?;
+
+            for job in jobs {
+                stmt.execute(params![
+                    to_sql_id(job.id.into_inner()),
+                    to_sql_id(job.run_id.into_inner()),
+                    job.name,
+                    job.created_at,
+                    job.started_at,
+                    job.completed_at,
+                    job.status.as_str(),
+                    job.conclusion.as_str(),
+                    job.html_url.as_str(),
+                    job.run_attempt as i64,
+                    job.head_sha,
+                    job.labels.join(","),
+                    now,
+                ])
+                .or_raise(make_error)?;
+            }
+        }
+        tx.commit().or_raise(make_error)
+    }
+
+    /// The runs displayed after the last successful refresh.
+    pub fn latest_snapshot(&self) -> Result<Vec<WorkflowRun>, StorageError> {
+        let make_error = || StorageError::from("error reading the latest snapshot");
+
+        let conn = self.conn();
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT {} FROM workflow_runs r
+                 JOIN latest_snapshot s ON s.run_id = r.id
+                 ORDER BY s.position",
+                prefixed(RUN_COLUMNS, "r")
+            ))
+            .or_raise(make_error)?;
+
+        let runs = stmt
+            .query_map([], run_from_row)
+            .or_raise(make_error)?
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .or_raise(make_error)?;
+
+        Ok(runs)
+    }
+
+    /// The time of the last successful refresh, if any.
+    pub fn latest_snapshot_time(&self) -> Result<Option<DateTime<Utc>>, StorageError> {
+        let make_error = || StorageError::from("error reading the latest snapshot");
+
+        let conn = self.conn();
+        conn.query_row(
+            "SELECT MAX(r.observed_at) FROM workflow_runs r
+             JOIN latest_snapshot s ON s.run_id = r.id",
+            [],
+            |row| row.get::<_, Option<DateTime<Utc>>>(0),
+        )
+        .optional()
+        .map(Option::flatten)
+        .or_raise(make_error)
+    }
+
+    /// The last `count` completed runs of the same workflow as `workflow`.
+    pub fn workflow_history(
+        &self,
+        workflow: &WorkflowRun,
+        count: u32,
+    ) -> Result<Vec<WorkflowRun>, StorageError> {
+        let make_error = || StorageError::from("error reading the workflow history");
+
+        let conn = self.conn();
+        let mut stmt = conn
+            .prepare_cached(&format!(
+                "SELECT {} FROM workflow_runs
+                 WHERE owner = ?1 AND repo = ?2 AND workflow_id = ?3 AND status = 'completed'
+                 ORDER BY start_time DESC
+                 LIMIT ?4",
+                RUN_COLUMNS
+            ))
+            .or_raise(make_error)?;
+
+        let runs = stmt
+            .query_map(
+                params![
+                    workflow.owner,
+                    workflow.repo,
+                    to_sql_id(workflow.workflow_id.into_inner()),
+                    count
+                ],
+                run_from_row,
+            )
+            .or_raise(make_error)?
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .or_raise(make_error)?;
+
+        Ok(runs)
+    }
+
+    /// The last known jobs of a run, of all its attempts.
+    pub fn jobs(&self, run_id: RunId) -> Result<Vec<WorkflowJob>, StorageError> {
+        let make_error = || StorageError::from("error reading workflow jobs");
+
+        let conn = self.conn();
+        let mut stmt = conn
+            .prepare_cached(&format!(
+                "SELECT {} FROM workflow_jobs WHERE run_id = ?1
+                 ORDER BY run_attempt, started_at, id",
+                JOB_COLUMNS
+            ))
+            .or_raise(make_error)?;
+
+        let jobs = stmt
+            .query_map(params![to_sql_id(run_id.into_inner())], job_from_row)
+            .or_raise(make_error)?
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .or_raise(make_error)?;
+
+        Ok(jobs)
+    }
+
+    /// The jobs of all the runs of a repository, with the name of their
+    /// workflow, ordered by the start of their run and then by attempt.
+    pub fn repo_jobs(
+        &self,
+        owner: &str,
+        repo: &str,
+    ) -> Result<Vec<(String, WorkflowJob)>, StorageError> {
+        let make_error = || StorageError::from("error reading the jobs of the repository");
+
+        let conn = self.conn();
+        let mut stmt = conn
+            .prepare_cached(&format!(
+                "SELECT {}, r.name FROM workflow_jobs j
+                 JOIN workflow_runs r ON r.id = j.run_id
+                 WHERE r.owner = ?1 AND r.repo = ?2
+                 ORDER BY r.start_time, r.id, j.run_attempt, j.started_at, j.id",
+                prefixed(JOB_COLUMNS, "j")
+            ))
+            .or_raise(make_error)?;
+
+        let jobs = stmt
+            .query_map(params![owner, repo], |row| {
+                Ok((row.get(12)?, job_from_row(row)?))
+            })
+            .or_raise(make_error)?
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .or_raise(make_error)?;
+
+        Ok(jobs)
+    }
+
+    /// The jobs of a workflow started since `since`, from the oldest.
+    pub fn workflow_jobs_since(
+        &self,
+        workflow: &WorkflowRun,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<WorkflowJob>, StorageError> {
+        let make_error = || StorageError::from("error reading the jobs of the workflow");
+
+        let conn = self.conn();
+        let mut stmt = conn
+            .prepare_cached(&format!(
+                "SELECT {} FROM workflow_jobs j
+                 JOIN workflow_runs r ON r.id = j.run_id
+                 WHERE r.owner = ?1 AND r.repo = ?2 AND r.workflow_id = ?3
+                   AND j.started_at >= ?4
+                 ORDER BY j.started_at, j.id",
+                prefixed(JOB_COLUMNS, "j")
+            ))
+            .or_raise(make_error)?;
+
+        let jobs = stmt
+            .query_map(
+                params![
+                    workflow.owner,
+                    workflow.repo,
+                    to_sql_id(workflow.workflow_id.into_inner()),
+                    since
+                ],
+                job_from_row,
+            )
+            .or_raise(make_error)?
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .or_raise(make_error)?;
+
+        Ok(jobs)
+    }
+
+    /// Delete the runs, and their jobs, older than the retention period.
+    ///
+    /// The runs of the latest snapshot are always kept.
+    pub fn prune(&self) -> Result<(), StorageError> {
+        let make_error = || StorageError::from("error pruning the history");
+
+        let cutoff = Utc::now() - chrono::TimeDelta::days(i64::from(self.retention_days));
+
+        let conn = self.conn();
+        let deleted = conn
+            .execute(
+                "DELETE FROM workflow_runs
+                 WHERE start_time < ?1 AND id NOT IN (SELECT run_id FROM latest_snapshot)",
+                params![cutoff],
+            )
+            .or_raise(make_error)?;
+        conn.execute(
+            "DELETE FROM workflow_jobs WHERE run_id NOT IN (SELECT id FROM workflow_runs)",
+            [],
+        )
+        .or_raise(make_error)?;
+
+        if deleted > 0 {
+            debug!("Pruned {} workflow runs from the history", deleted);
+        }
+
+        Ok(())
+    }
+}
+
+fn default_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|d| d.join("gh-dashboard").join("history.sqlite"))
+}
+
+fn migrate(conn: &mut Connection) -> Result<(), StorageError> {
+    let make_error = || StorageError::from("error migrating the history schema");
+
+    let version: u32 = conn
+        .pragma_query_value(None, "user_version", |row| row.get(0))
+        .or_raise(make_error)?;
+
+    for (idx, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        let target = idx as u32 + 1;
+        debug!("Migrating history schema to version {}", target);
+
+        let tx = conn.transaction().or_raise(make_error)?;
+        tx.execute_batch(migration).or_raise(make_error)?;
+        tx.pragma_update(None, "user_version", target)
+            .or_raise(make_error)?;
+        tx.commit().or_raise(make_error)?;
+    }
+
+    Ok(())
+}
+
+fn upsert_runs(conn: &Connection, runs: &[WorkflowRun]) -> rusqlite::Result<()> {
+    let now = Utc::now();
+    let mut stmt = conn.prepare_cached(&format!(
+        "INSERT OR REPLACE INTO workflow_runs ({}, observed_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, \
+         ?19, ?20, ?21, ?22, ?23, ?24)",
+        RUN_COLUMNS
+    ))?;
+
+    for run in runs {
+        stmt.execute(params![
+            to_sql_id(run.id.into_inner()),
+            to_sql_id(run.workflow_id.into_inner()),
+            run.owner,
+            run.repo,
+            run.branch,
+            run.name,
+            run.commit_message,
+            run.start_time,
+            run.run_started_at,
+            run.updated_at,
+            run.status.as_str(),
+            run.conclusion.as_str(),
+            run.html_url.as_str(),
+            run.head_sha,
+            run.commit_author,
+            run.event,
+            run.actor,
+            run.triggering_actor,
+            run.run_number as i64,
+            run.run_attempt as i64,
+            run.pull_request.as_ref().map(|pr| pr.number as i64),
+            run.pull_request.as_ref().and_then(|pr| pr.title.clone()),
+            run.check_suite_id.map(to_sql_id),
+            now,
+        ])?;
+    }
+
+    Ok(())
+}
+
+fn run_from_row(row: &Row<'_>) -> rusqlite::Result<WorkflowRun> {
+    let status: String = row.get(10)?;
+    let conclusion: Option<String> = row.get(11)?;
+
+    Ok(WorkflowRun {
+        id: RunId::from(from_sql_id(row.get(0)?)),
+        workflow_id: WorkflowId::from(from_sql_id(row.get(1)?)),
+        owner: row.get(2)?,
+        repo: row.get(3)?,
+        branch: row.get(4)?,
+        name: row.get(5)?,
+        commit_message: row.get(6)?,
+        start_time: row.get(7)?,
+        run_started_at: row.get(8)?,
+        updated_at: row.get(9)?,
+        status: WorkflowRunStatus::from(status.as_str()),
+        conclusion: conclusion.map_or(WorkflowRunConclusion::default(), |c| {
+            WorkflowRunConclusion::from(c.as_str())
+        }),
+        html_url: parse_url(row, 12)?,
+        head_sha: row.get(13)?,
+        commit_author: row.get(14)?,
+        event: row.get(15)?,
+        actor: row.get(16)?,
+        triggering_actor: row.get(17)?,
+        run_number: row.get::<_, i64>(18)? as u64,
+        run_attempt: row.get::<_, i64>(19)? as u64,
+        pull_request: row
+            .get::<_, Option<i64>>(20)?
+            .map(|number| -> rusqlite::Result<_> {
+                Ok(PullRequest {
+                    number: number as u64,
+                    title: row.get(21)?,
+                })
+            })
+            .transpose()?,
+        check_suite_id: row.get::<_, Option<i64>>(22)?.map(from_sql_id),
+    })
+}
+
+fn job_from_row(row: &Row<'_>) -> rusqlite::Result<WorkflowJob> {
+    let status: String = row.get(6)?;
+    let conclusion: String = row.get(7)?;
+
+    Ok(WorkflowJob {
+        id: JobId::from(from_sql_id(row.get(0)?)),
+        run_id: RunId::from(from_sql_id(row.get(1)?)),
+        name: row.get(2)?,
+        created_at: row.get(3)?,
+        started_at: row.get(4)?,
+        completed_at: row.get(5)?,
+        status: WorkflowJobStatus::from(status.as_str()),
+        conclusion: WorkflowJobConclusion::from(conclusion.as_str()),
+        html_url: parse_url(row, 8)?,
+        run_attempt: row.get::<_, i64>(9)? as u64,
+        head_sha: row.get(10)?,
+        labels: row
+            .get::<_, String>(11)?
+            .split(',')
+            .filter(|l| !l.is_empty())
+            .map(str::to_string)
+            .collect(),
+    })
+}
+
+fn parse_url(row: &Row<'_>, idx: usize) -> rusqlite::Result<url::Url> {
+    let value: String = row.get(idx)?;
+
+    url::Url::parse(&value).map_err(|e| {
+        rusqlite::Error::FromSqlConversionFailure(idx, rusqlite::types::Type::Text, Box::new(e))
+    })
+}
+
+fn prefixed(columns: &str, table: &str) -> String {
+    columns
+        .split(',')
+        .map(|c| format!("{}.{}", table, c.trim()))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+// SQLite integers are signed, GitHub ids fit comfortably in an i64.
+fn to_sql_id(id: u64) -> i64 {
+    id as i64
+}
+
+fn from_sql_id(id: i64) -> u64 {
+    id as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user_version(conn: &Connection) -> usize {
+        conn.pragma_query_value(None, "user_version", |row| row.get::<_, u32>(0))
+            .unwrap() as usize
+    }
+
+    #[test]
+    fn migrate_an_empty_database() {
+        let mut conn = Connection::open_in_memory().unwrap();
+
+        migrate(&mut conn).unwrap();
+
+        assert_eq!(user_version(&conn), MIGRATIONS.len());
+        conn.prepare(&format!("SELECT {} FROM workflow_runs", RUN_COLUMNS))
+            .unwrap();
+        conn.prepare(&format!("SELECT {} FROM workflow_jobs", JOB_COLUMNS))
+            .unwrap();
+    }
+
+    #[test]
+    fn migrate_a_migrated_database() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        migrate(&mut conn).unwrap();
+
+        migrate(&mut conn).unwrap();
+
+        assert_eq!(user_version(&conn), MIGRATIONS.len());
+    }
+}
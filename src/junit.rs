@@ -0,0 +1,214 @@
+use std::path::Path;
+
+use exn::{Result, ResultExt};
+use log::warn;
+use roxmltree::{Document, Node};
+
+use crate::error::AppError;
+
+/// The outcome of the tests of one or more JUnit XML reports.
+#[derive(Debug, Clone, Default)]
+pub struct TestReport {
+    pub passed: usize,
+    pub failed: usize,
+    pub skipped: usize,
+    /// The tests that failed or errored, in the order of the reports.
+    pub failures: Vec<TestFailure>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TestFailure {
+    /// The class of the test and its name, e.g. `tests.api.test_login`.
+    pub name: String,
+    pub message: String,
+}
+
+impl TestReport {
+    pub fn total(&self) -> usize {
+        self.passed + self.failed + self.skipped
+    }
+
+    pub fn merge(&mut self, other: TestReport) {
+        self.passed += other.passed;
+        self.failed += other.failed;
+        self.skipped += other.skipped;
+        self.failures.extend(other.failures);
+    }
+}
+
+/// Whether the name of an artifact suggests it holds test reports, only those
+/// are downloaded to look for them.
+pub fn is_test_artifact(name: &str) -> bool {
+    let name = name.to_lowercase();
+    ["test", "junit", "report", "result"]
+        .iter()
+        .any(|w| name.contains(w))
+}
+
+/// Parse a JUnit XML report, `None` when the document isn't one.
+///
+/// The `<testcase>` elements are counted rather than the totals of the
+/// `<testsuite>` elements, which not every tool writes.
+pub fn parse(xml: &str) -> Result<Option<TestReport>, AppError> {
+    let make_error = || AppError::from("failed to parse the JUnit report");
+
+    let document = Document::parse(xml).or_raise(make_error)?;
+    let root = document.root_element();
+    if !matches!(root.tag_name().name(), "testsuites" | "testsuite") {
+        return Ok(None);
+    }
+
+    let mut report = TestReport::default();
+
+    for testcase in root.descendants().filter(|n| n.has_tag_name("testcase")) {
+        if let Some(failure) = testcase
+            .children()
+            .find(|n| n.has_tag_name("failure") || n.has_tag_name("error"))
+        {
+            report.failed += 1;
+            report.failures.push(TestFailure {
+                name: test_name(testcase),
+                message: failure_message(failure),
+            });
+        } else if testcase.children().any(|n| n.has_tag_name("skipped")) {
+            report.skipped += 1;
+        } else {
+            report.passed += 1;
+        }
+    }
+
+    Ok(Some(report))
+}
+
+/// Find and parse the JUnit reports under `directory`, `None` when there are
+/// none.
+///
+/// The XML files that aren't JUnit reports, or can't be parsed, are skipped.
+pub fn load(directory: &Path) -> Result<Option<TestReport>, AppError> {
+    let make_error = || AppError::from(format!("failed to read {}", directory.display()));
+
+    let mut report: Option<TestReport> = None;
+    let mut directories = vec![directory.to_path_buf()];
+
+    while let Some(directory) = directories.pop() {
+        for entry in std::fs::read_dir(&directory).or_raise(make_error)? {
+            let path = entry.or_raise(make_error)?.path();
+
+            if path.is_dir() {
+                directories.push(path);
+                continue;
+            }
+            if path.extension().is_none_or(|e| e != "xml") {
+                continue;
+            }
+
+            let parsed = std::fs::read_to_string(&path)
+                .or_raise(make_error)
+                .and_then(|xml| parse(&xml));
+            match parsed {
+                Ok(Some(r)) => report.get_or_insert_default().merge(r),
+                Ok(None) => {}
+                Err(err) => warn!("Skipping {}: {:?}", path.display(), err),
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+fn test_name(testcase: Node) -> String {
+    let name = testcase.attribute("name").unwrap_or_default();
+
+    match testcase.attribute("classname") {
+        Some(class) if !class.is_empty() => format!("{}.{}", class, name),
+        _ => name.to_string(),
+    }
+}
+
+/// The message attribute, or else the first line of the details.
+fn failure_message(failure: Node) -> String {
+    failure
+        .attribute("message")
+        .filter(|m| !m.trim().is_empty())
+        .or_else(|| {
+            failure
+                .text()
+                .and_then(|t| t.lines().map(str::trim).find(|l| !l.is_empty()))
+        })
+        .unwrap_or_default()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_report() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+            <testsuites>
+                <testsuite name="api">
+                    <testcase classname="tests.api" name="test_login"/>
+                    <testcase classname="tests.api" name="test_logout">
+                        <failure message="expected 200, got 500">stack trace</failure>
+                    </testcase>
+                    <testcase name="test_timeout">
+                        <error>
+                            Timed out after 30s
+                            at tests/api.rs:12
+                        </error>
+                    </testcase>
+                    <testcase classname="tests.api" name="test_admin">
+                        <skipped/>
+                    </testcase>
+                </testsuite>
+            </testsuites>"#;
+
+        let report = parse(xml).unwrap().unwrap();
+
+        assert_eq!(
+            (report.passed, report.failed, report.skipped, report.total()),
+            (1, 2, 1, 4)
+        );
+        let failures: Vec<_> = report
+            .failures
+            .iter()
+            .map(|f| (f.name.as_str(), f.message.as_str()))
+            .collect();
+        assert_eq!(
+            failures,
+            vec![
+                ("tests.api.test_logout", "expected 200, got 500"),
+                ("test_timeout", "Timed out after 30s"),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_other_documents() {
+        assert!(parse("<coverage/>").unwrap().is_none());
+        assert!(parse("<testsuite>").is_err());
+    }
+
+    #[test]
+    fn merge_reports() {
+        let mut report = parse(r#"<testsuite><testcase name="a"/></testsuite>"#)
+            .unwrap()
+            .unwrap();
+        report.merge(
+            parse(r#"<testsuite><testcase name="b"><failure/></testcase></testsuite>"#)
+                .unwrap()
+                .unwrap(),
+        );
+
+        assert_eq!((report.passed, report.failed), (1, 1));
+        assert_eq!(report.failures[0].name, "b");
+    }
+
+    #[test]
+    fn detect_test_artifacts() {
+        assert!(is_test_artifact("JUnit-results"));
+        assert!(is_test_artifact("test-reports-linux"));
+        assert!(!is_test_artifact("dist"));
+    }
+}
@@ -0,0 +1,589 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::time::{Duration, Instant};
+
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+use serde::Deserialize;
+
+/// What the user can do with the keyboard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Quit,
+    Help,
+    ScrollDown,
+    ScrollUp,
+    PageDown,
+    PageUp,
+    First,
+    Last,
+    Open,
+    Details,
+    Refresh,
+    FlakyJobs,
+    Close,
+    SwitchFocus,
+    CopyUrl,
+    CopySha,
+    CopyBranch,
+    CopySummary,
+    RerunJob,
+    Artifacts,
+    Download,
+    TestResults,
+    Runners,
+    Queue,
+    Usage,
+}
+
+impl Action {
+    /// All the actions, in the order they are listed in the help.
+    pub const ALL: [Action; 25] = [
+        Action::ScrollDown,
+        Action::ScrollUp,
+        Action::PageDown,
+        Action::PageUp,
+        Action::First,
+        Action::Last,
+        Action::Open,
+        Action::CopyUrl,
+        Action::CopySha,
+        Action::CopyBranch,
+        Action::CopySummary,
+        Action::Details,
+        Action::SwitchFocus,
+        Action::Close,
+        Action::RerunJob,
+        Action::Artifacts,
+        Action::Download,
+        Action::TestResults,
+        Action::FlakyJobs,
+        Action::Runners,
+        Action::Queue,
+        Action::Usage,
+        Action::Refresh,
+        Action::Help,
+        Action::Quit,
+    ];
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Action::Quit => "Quit",
+            Action::Help => "Show or hide this help",
+            Action::ScrollDown => "Select the next run or job",
+            Action::ScrollUp => "Select the previous run or job",
+            Action::PageDown => "Scroll down by a page",
+            Action::PageUp => "Scroll up by a page",
+            Action::First => "Select the first run or job",
+            Action::Last => "Select the last run or job",
+            Action::Open => "Open the run or the job in the browser",
+            Action::Details => "Show the details of the run",
+            Action::Refresh => "Refresh now",
+            Action::FlakyJobs => "Show the flakiest jobs of the repository",
+            Action::Close => "Close the details or the help",
+            Action::SwitchFocus => "Switch between the runs and the jobs",
+            Action::CopyUrl => "Copy the URL of the run or the job",
+            Action::CopySha => "Copy the commit SHA of the run",
+            Action::CopyBranch => "Copy the branch of the run",
+            Action::CopySummary => "Copy a one-line summary of the run",
+            Action::RerunJob => "Re-run the selected job",
+            Action::Artifacts => "Show or hide the artifacts of the run",
+            Action::Download => "Download the selected artifact",
+            Action::TestResults => "Show or hide the test results of the run",
+            Action::Runners => "Show or hide the self-hosted runners",
+            Action::Queue => "Show or hide the jobs waiting for a runner",
+            Action::Usage => "Show or hide the Actions minutes of the workflows",
+        }
+    }
+
+    fn default_keys(&self) -> &'static [&'static str] {
+        match self {
+            Action::Quit => &["q"],
+            Action::Help => &["?"],
+            Action::ScrollDown => &["j", "down"],
+            Action::ScrollUp => &["k", "up"],
+            Action::PageDown => &["pagedown", "ctrl-d"],
+            Action::PageUp => &["pageup", "ctrl-u"],
+            Action::First => &["g g", "home"],
+            Action::Last => &["G", "end"],
+            Action::Open => &["enter"],
+            Action::Details => &["d"],
+            Action::Refresh => &["r"],
+            Action::FlakyJobs => &["f"],
+            Action::Close => &["esc"],
+            Action::SwitchFocus => &["tab"],
+            Action::CopyUrl => &["y y"],
+            Action::CopySha => &["y s"],
+            Action::CopyBranch => &["y b"],
+            Action::CopySummary => &["y l"],
+            Action::RerunJob => &["R"],
+            Action::Artifacts => &["a"],
+            Action::Download => &["D"],
+            Action::TestResults => &["t"],
+            Action::Runners => &["s"],
+            Action::Queue => &["w"],
+            Action::Usage => &["u"],
+        }
+    }
+}
+
+/// What the widgets receive from the app: the actions bound to the keys
+/// pressed, and the other terminal events.
+#[derive(Debug, Clone)]
+pub enum Input {
+    Action(Action),
+    Event(Event),
+}
+
+/// A key with its modifiers, e.g. `ctrl-d`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyPress {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl KeyPress {
+    const NAMED_KEYS: [(&'static str, KeyCode); 13] = [
+        ("enter", KeyCode::Enter),
+        ("esc", KeyCode::Esc),
+        ("tab", KeyCode::Tab),
+        ("backspace", KeyCode::Backspace),
+        ("space", KeyCode::Char(' ')),
+        ("up", KeyCode::Up),
+        ("down", KeyCode::Down),
+        ("left", KeyCode::Left),
+        ("right", KeyCode::Right),
+        ("pageup", KeyCode::PageUp),
+        ("pagedown", KeyCode::PageDown),
+        ("home", KeyCode::Home),
+        ("end", KeyCode::End),
+    ];
+
+    fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        // The case of a character already tells if shift is pressed, and the
+        // terminals send shift-tab as a key of its own.
+        let (code, modifiers) = match code {
+            KeyCode::Char(_) | KeyCode::BackTab => (code, modifiers - KeyModifiers::SHIFT),
+            KeyCode::Tab if modifiers.contains(KeyModifiers::SHIFT) => {
+                (KeyCode::BackTab, modifiers - KeyModifiers::SHIFT)
+            }
+            _ => (code, modifiers),
+        };
+
+        Self { code, modifiers }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = value;
+
+        // A single `-` is a key, not a separator.
+        while let Some((modifier, key)) = rest.split_once('-')
+            && !key.is_empty()
+        {
+            modifiers |= match modifier.to_lowercase().as_str() {
+                "ctrl" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return None,
+            };
+            rest = key;
+        }
+
+        let mut chars = rest.chars();
+        let code = match (chars.next(), chars.next()) {
+            // Shift turns a letter into its upper case, the other characters
+            // it changes depend on the keyboard layout.
+            (Some(c), None) if modifiers.contains(KeyModifiers::SHIFT) => {
+                let mut upper = c.to_uppercase();
+                match (upper.next(), upper.next()) {
+                    (Some(u), None) if c.is_alphabetic() => KeyCode::Char(u),
+                    _ => return None,
+                }
+            }
+            (Some(c), None) => KeyCode::Char(c),
+            _ => {
+                let name = rest.to_lowercase();
+                match Self::NAMED_KEYS.iter().find(|(n, _)| *n == name) {
+                    Some((_, code)) => *code,
+                    None => KeyCode::F(name.strip_prefix('f')?.parse().ok()?),
+                }
+            }
+        };
+
+        Some(Self::new(code, modifiers))
+    }
+}
+
+impl From<&KeyEvent> for KeyPress {
+    fn from(key: &KeyEvent) -> Self {
+        Self::new(key.code, key.modifiers)
+    }
+}
+
+impl Display for KeyPress {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (modifier, name) in [
+            (KeyModifiers::CONTROL, "ctrl-"),
+            (KeyModifiers::ALT, "alt-"),
+            (KeyModifiers::SHIFT, "shift-"),
+        ] {
+            if self.modifiers.contains(modifier) {
+                write!(f, "{}", name)?;
+            }
+        }
+
+        match (
+            Self::NAMED_KEYS.iter().find(|(_, c)| *c == self.code),
+            self.code,
+        ) {
+            (Some((name, _)), _) => write!(f, "{}", name),
+            (None, KeyCode::BackTab) => write!(f, "shift-tab"),
+            (None, KeyCode::Char(c)) => write!(f, "{}", c),
+            (None, KeyCode::F(n)) => write!(f, "f{}", n),
+            (None, code) => write!(f, "{:?}", code),
+        }
+    }
+}
+
+/// The keys to press in order to trigger an action, e.g. `g g` or `ctrl-d`.
+///
+/// The keys are separated by spaces. A word that isn't the name of a key, such
+/// as `gg`, is a sequence of characters.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct KeySequence(Vec<KeyPress>);
+
+impl TryFrom<String> for KeySequence {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let make_err = || format!("invalid key {}", value);
+
+        let mut keys = vec![];
+
+        for word in value.split_whitespace() {
+            match KeyPress::parse(word) {
+                Some(key) => keys.push(key),
+                None if word.chars().all(|c| c.is_alphanumeric()) => {
+                    keys.extend(
+                        word.chars()
+                            .map(|c| KeyPress::new(KeyCode::Char(c), KeyModifiers::NONE)),
+                    );
+                }
+                None => return Err(make_err()),
+            }
+        }
+
+        if keys.is_empty() {
+            return Err(make_err());
+        }
+
+        Ok(Self(keys))
+    }
+}
+
+impl Display for KeySequence {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let keys: Vec<_> = self.0.iter().map(|k| k.to_string()).collect();
+        write!(f, "{}", keys.join(" "))
+    }
+}
+
+/// Resolves the keys pressed into actions.
+///
+/// The keys of a sequence must be pressed within `CHORD_TIMEOUT` of each other.
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    bindings: Vec<(KeySequence, Action)>,
+    pending: Vec<KeyPress>,
+    last_key_at: Option<Instant>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::new(&HashMap::new())
+    }
+}
+
+impl Keymap {
+    const CHORD_TIMEOUT: Duration = Duration::from_secs(1);
+
+    /// Build the keymap from the configured keys. The keys of an action replace
+    /// its default keys.
+    pub fn new(keys: &HashMap<Action, Vec<KeySequence>>) -> Self {
+        let bindings = Action::ALL
+            .iter()
+            .flat_map(|action| {
+                let sequences = keys.get(action).cloned().unwrap_or_else(|| {
+                    action
+                        .default_keys()
+                        .iter()
+                        .map(|k| KeySequence::try_from(k.to_string()).unwrap())
+                        .collect()
+                });
+
+                sequences.into_iter().map(|s| (s, *action))
+            })
+            .collect();
+
+        Self {
+            bindings,
+            pending: vec![],
+            last_key_at: None,
+        }
+    }
+
+    /// The bindings that can never be triggered: a sequence bound to several
+    /// actions only triggers the first one, and a sequence is shadowed by the
+    /// sequences it starts with, e.g. `g g` by `g`.
+    pub fn conflicts(&self) -> Vec<String> {
+        let mut conflicts = vec![];
+
+        for (i, (sequence, action)) in self.bindings.iter().enumerate() {
+            for (other, other_action) in &self.bindings[i + 1..] {
+                if action == other_action {
+                    continue;
+                }
+
+                if sequence == other {
+                    conflicts.push(format!(
+                        "{} is bound to both {:?} and {:?}",
+                        sequence, action, other_action
+                    ));
+                } else if other.0.starts_with(&sequence.0) {
+                    conflicts.push(format!(
+                        "{} of {:?} shadows {} of {:?}",
+                        sequence, action, other, other_action
+                    ));
+                } else if sequence.0.starts_with(&other.0) {
+                    conflicts.push(format!(
+                        "{} of {:?} shadows {} of {:?}",
+                        other, other_action, sequence, action
+                    ));
+                }
+            }
+        }
+
+        conflicts
+    }
+
+    /// The keys bound to an action.
+    pub fn keys(&self, action: Action) -> Vec<&KeySequence> {
+        self.bindings
+            .iter()
+            .filter(|(_, a)| *a == action)
+            .map(|(s, _)| s)
+            .collect()
+    }
+
+    /// A short description of the keys of an action, for the hints, e.g.
+    /// `q to quit`.
+    pub fn hint(&self, action: Action, text: &str) -> Option<String> {
+        let keys = self.keys(action);
+        keys.first().map(|k| format!("{} {}", k, text))
+    }
+
+    /// Feed a key press, returns the action once a whole sequence is pressed.
+    pub fn resolve(&mut self, key: &KeyEvent) -> Option<Action> {
+        let now = Instant::now();
+        if self
+            .last_key_at
+            .is_some_and(|t| now - t > Self::CHORD_TIMEOUT)
+        {
+            self.pending.clear();
+        }
+        self.last_key_at = Some(now);
+
+        self.pending.push(key.into());
+
+        if let Some(action) = self.matching(&self.pending) {
+            self.pending.clear();
+            return Some(action);
+        }
+
+        if self.is_prefix(&self.pending) {
+            return None;
+        }
+
+        // The sequence can't be completed, the key may start a new one.
+        self.pending = vec![key.into()];
+
+        if let Some(action) = self.matching(&self.pending) {
+            self.pending.clear();
+            return Some(action);
+        }
+
+        if !self.is_prefix(&self.pending) {
+            self.pending.clear();
+        }
+
+        None
+    }
+
+    fn matching(&self, keys: &[KeyPress]) -> Option<Action> {
+        self.bindings
+            .iter()
+            .find(|(s, _)| s.0 == keys)
+            .map(|(_, a)| *a)
+    }
+
+    fn is_prefix(&self, keys: &[KeyPress]) -> bool {
+        self.bindings
+            .iter()
+            .any(|(s, _)| s.0.len() > keys.len() && s.0.starts_with(keys))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    fn sequence(value: &str) -> KeySequence {
+        KeySequence::try_from(value.to_string()).unwrap()
+    }
+
+    fn press(keymap: &mut Keymap, c: char) -> Option<Action> {
+        keymap.resolve(&KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE))
+    }
+
+    #[test]
+    fn all_actions_are_listed_once() {
+        // A new action doesn't compile here until it's listed in `ALL` too.
+        let listed = |action: Action| match action {
+            Action::Quit
+            | Action::Help
+            | Action::ScrollDown
+            | Action::ScrollUp
+            | Action::PageDown
+            | Action::PageUp
+            | Action::First
+            | Action::Last
+            | Action::Open
+            | Action::Details
+            | Action::Refresh
+            | Action::FlakyJobs
+            | Action::Close
+            | Action::SwitchFocus
+            | Action::CopyUrl
+            | Action::CopySha
+            | Action::CopyBranch
+            | Action::CopySummary
+            | Action::RerunJob
+            | Action::Artifacts
+            | Action::Download
+            | Action::TestResults
+            | Action::Runners
+            | Action::Queue
+            | Action::Usage => Action::ALL.contains(&action),
+        };
+
+        assert!(Action::ALL.into_iter().all(listed));
+        assert_eq!(
+            Action::ALL.into_iter().collect::<HashSet<_>>().len(),
+            Action::ALL.len()
+        );
+    }
+
+    #[test]
+    fn default_keys_parse_and_dont_conflict() {
+        for action in Action::ALL {
+            assert!(!action.description().is_empty(), "{:?}", action);
+            assert!(!action.default_keys().is_empty(), "{:?}", action);
+            for key in action.default_keys() {
+                assert!(
+                    KeySequence::try_from(key.to_string()).is_ok(),
+                    "{:?}: {}",
+                    action,
+                    key
+                );
+            }
+        }
+
+        assert_eq!(Keymap::default().conflicts(), Vec::<String>::new());
+    }
+
+    #[test]
+    fn parse_key_sequences() {
+        assert_eq!(
+            sequence("ctrl-d"),
+            KeySequence(vec![KeyPress::new(
+                KeyCode::Char('d'),
+                KeyModifiers::CONTROL
+            )])
+        );
+        assert_eq!(sequence("gg"), sequence("g g"));
+        assert_eq!(sequence("-").to_string(), "-");
+        assert_eq!(sequence("shift-tab").to_string(), "shift-tab");
+        assert_eq!(sequence("F5").to_string(), "f5");
+        assert_eq!(sequence("pagedown y").to_string(), "pagedown y");
+
+        assert!(KeySequence::try_from(String::new()).is_err());
+        assert!(KeySequence::try_from("hyper-x".to_string()).is_err());
+        assert!(KeySequence::try_from("g!".to_string()).is_err());
+    }
+
+    #[test]
+    fn parse_shifted_keys() {
+        assert_eq!(sequence("shift-g"), sequence("G"));
+        assert_eq!(sequence("ctrl-shift-g"), sequence("ctrl-G"));
+        assert_ne!(sequence("shift-g"), sequence("g"));
+        assert!(KeySequence::try_from("shift-1".to_string()).is_err());
+
+        assert_eq!(
+            sequence("shift-tab"),
+            KeySequence(vec![KeyPress::new(KeyCode::BackTab, KeyModifiers::NONE)])
+        );
+    }
+
+    #[test]
+    fn resolve_shifted_keys() {
+        let keys = HashMap::from([
+            (Action::Refresh, vec![sequence("shift-r")]),
+            (Action::SwitchFocus, vec![sequence("shift-tab")]),
+            (Action::RerunJob, vec![sequence("ctrl-r")]),
+        ]);
+        let mut keymap = Keymap::new(&keys);
+
+        // The terminals send the upper case character with shift.
+        let key = KeyEvent::new(KeyCode::Char('R'), KeyModifiers::SHIFT);
+        assert_eq!(keymap.resolve(&key), Some(Action::Refresh));
+        let key = KeyEvent::new(KeyCode::BackTab, KeyModifiers::SHIFT);
+        assert_eq!(keymap.resolve(&key), Some(Action::SwitchFocus));
+        assert_eq!(press(&mut keymap, 'r'), None);
+    }
+
+    #[test]
+    fn resolve_sequences() {
+        let mut keymap = Keymap::default();
+
+        assert_eq!(press(&mut keymap, 'j'), Some(Action::ScrollDown));
+        assert_eq!(press(&mut keymap, 'g'), None);
+        assert_eq!(press(&mut keymap, 'g'), Some(Action::First));
+        assert_eq!(press(&mut keymap, 'y'), None);
+        assert_eq!(press(&mut keymap, 's'), Some(Action::CopySha));
+
+        // A key that can't complete the sequence starts a new one.
+        assert_eq!(press(&mut keymap, 'g'), None);
+        assert_eq!(press(&mut keymap, 'q'), Some(Action::Quit));
+        assert_eq!(press(&mut keymap, 'x'), None);
+        assert_eq!(press(&mut keymap, 'j'), Some(Action::ScrollDown));
+    }
+
+    #[test]
+    fn detect_conflicts() {
+        let keys = HashMap::from([(Action::Refresh, vec![sequence("g")])]);
+        assert_eq!(
+            Keymap::new(&keys).conflicts(),
+            vec!["g of Refresh shadows g g of First"]
+        );
+
+        let keys = HashMap::from([(Action::Refresh, vec![sequence("q")])]);
+        assert_eq!(
+            Keymap::new(&keys).conflicts(),
+            vec!["q is bound to both Refresh and Quit"]
+        );
+    }
+}
@@ -1,29 +1,53 @@
-use std::panic;
+use std::io::stdout;
 use std::sync::Arc;
 use std::time::Duration;
-
-use crossterm::event::{Event, EventStream, KeyCode};
+use std::{panic, thread};
+
+use crossterm::event::{
+    DisableFocusChange,
+    DisableMouseCapture,
+    EnableFocusChange,
+    EnableMouseCapture,
+    Event,
+    EventStream,
+};
+use crossterm::execute;
 use exn::{Result, ResultExt};
-use log::error;
+use log::{error, warn};
 use octocrab::Octocrab;
-use ratatui::layout::{Constraint, Layout};
-use ratatui::style::Stylize;
+use ratatui::layout::{Constraint, Layout, Rect};
 use ratatui::text::Line;
+use ratatui::widgets::{Block, Clear, Row, Table};
 use ratatui::{DefaultTerminal, Frame};
 use tokio::sync::mpsc;
 use tokio_stream::StreamExt;
 
 use crate::error::AppError;
+use crate::history::HistoryStore;
+use crate::keymap::{Action, Input, Keymap};
 use crate::service::workflows;
 use crate::service::workflows::GitHubService;
+use crate::widgets::theme::Theme;
 use crate::widgets::workflow_run::WorkflowRunListWidget;
 
+mod artifacts;
+mod backoff;
+mod browser;
+mod clipboard;
 mod configuration;
 mod error;
+mod flaky;
+mod history;
+mod junit;
+mod keymap;
 mod models;
 mod service;
+mod session;
+mod stats;
+mod supervisor;
 #[cfg(any(test, feature = "mocks"))]
 mod testing;
+mod usage;
 mod widgets;
 
 fn make_error() -> AppError {
@@ -42,11 +66,41 @@ async fn main() -> Result<(), AppError> {
 
     init_github_client(&cfg).await?;
 
+    let history = match HistoryStore::from_settings(&cfg.history) {
+        Ok(h) => h.map(Arc::new),
+        Err(e) => {
+            error!("Unable to open the history store: {}", e);
+            None
+        }
+    };
+
     color_eyre::install()
         .map_err(AppError::from_color_eyre)
         .or_raise(make_error)?;
     let terminal = ratatui::init();
-    let app_result = App::new(cfg).run(terminal).await;
+    // The background tasks are restarted when they panic, only a panic of the
+    // UI restores the terminal and reports the panic.
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        if thread::current().name() == Some("main") {
+            // The terminal would keep reporting the mouse and the focus to
+            // the shell.
+            let _ = execute!(stdout(), DisableMouseCapture, DisableFocusChange);
+            hook(info);
+        } else {
+            error!("A background task panicked: {}", info);
+        }
+    }));
+    // Focus events are used to slow down the refresh when the terminal is
+    // unfocused.
+    if let Err(e) = execute!(stdout(), EnableFocusChange) {
+        warn!("Unable to enable focus events: {}", e);
+    }
+    if let Err(e) = execute!(stdout(), EnableMouseCapture) {
+        warn!("Unable to enable mouse events: {}", e);
+    }
+    let app_result = App::new(cfg, history).run(terminal).await;
+    let _ = execute!(stdout(), DisableMouseCapture, DisableFocusChange);
     ratatui::restore();
 
     app_result
@@ -64,8 +118,13 @@ async fn init_github_client(cfg: &configuration::Settings) -> Result<(), AppErro
         .build()
         .unwrap();
 
-    // Validate the token
-    crab.current().user().await.or_raise(make_error)?;
+    // Validate the token. If GitHub can't be reached, start anyway with the
+    // cached data: the widgets keep retrying in the background.
+    match crab.current().user().await {
+        Ok(_) => {}
+        Err(e @ octocrab::Error::GitHub { .. }) => Err(e).or_raise(make_error)?,
+        Err(e) => warn!("Unable to reach GitHub, starting offline: {}", e),
+    }
 
     octocrab::initialise(crab);
 
@@ -75,17 +134,22 @@ async fn init_github_client(cfg: &configuration::Settings) -> Result<(), AppErro
 #[derive(Debug, Default)]
 struct App {
     should_quit: bool,
+    show_help: bool,
+    keymap: Keymap,
+    theme: Theme,
     workflow_run_widgets: WorkflowRunListWidget,
 }
 
 impl App {
     const FRAMES_PER_SECOND: f32 = 60.0;
 
-    fn new(config: configuration::Settings) -> Self {
+    fn new(config: configuration::Settings, history: Option<Arc<HistoryStore>>) -> Self {
         let github_service = get_github_service();
 
         Self {
-            workflow_run_widgets: WorkflowRunListWidget::new(github_service, config.repos),
+            keymap: Keymap::new(&config.keys),
+            theme: Theme::new(&config.theme),
+            workflow_run_widgets: WorkflowRunListWidget::new(github_service, &config, history),
             ..Default::default()
         }
     }
@@ -109,21 +173,66 @@ impl App {
     fn render(&self, frame: &mut Frame) {
         let layout = Layout::vertical([Constraint::Length(1), Constraint::Fill(1)]);
         let [title_area, body_area] = frame.area().layout(&layout);
-        let title = Line::from("GitHub Workflow Dashboard").centered().bold();
+        let title = Line::from("GitHub Workflow Dashboard")
+            .centered()
+            .style(self.theme.title);
         frame.render_widget(title, title_area);
         frame.render_widget(&self.workflow_run_widgets, body_area);
+
+        if self.show_help {
+            self.render_help(frame, body_area);
+        }
     }
 
-    async fn handle_event(&mut self, event: &Event, tx: &mpsc::Sender<Event>) {
-        if let Some(key) = event.as_key_press_event() {
-            #[allow(clippy::collapsible_if)]
-            if let KeyCode::Char('q') = key.code {
-                self.should_quit = true
-            }
+    /// The help lists the keys of the active keymap.
+    fn render_help(&self, frame: &mut Frame, area: Rect) {
+        let rows: Vec<_> = Action::ALL
+            .iter()
+            .map(|action| {
+                let keys: Vec<_> = self
+                    .keymap
+                    .keys(*action)
+                    .iter()
+                    .map(|k| k.to_string())
+                    .collect();
+
+                Row::new(vec![keys.join(", "), action.description().to_string()])
+            })
+            .collect();
+
+        let height = rows.len() as u16 + 2;
+        let area = area.centered(Constraint::Length(64), Constraint::Length(height));
+
+        let mut block = Block::bordered().title("Keys");
+        if let Some(hint) = self.keymap.hint(Action::Close, "to close") {
+            block = block.title_bottom(hint);
         }
+        let table = Table::new(rows, [Constraint::Length(24), Constraint::Fill(1)]).block(block);
 
-        if let Err(e) = tx.send(event.clone()).await {
-            error!("Failed to send event to workflow run widget: {}", e);
+        frame.render_widget(Clear, area);
+        frame.render_widget(table, area);
+    }
+
+    async fn handle_event(&mut self, event: &Event, tx: &mpsc::Sender<Input>) {
+        let input = match event.as_key_press_event() {
+            Some(key) => match self.keymap.resolve(&key) {
+                Some(action) => Input::Action(action),
+                None => return,
+            },
+            None => Input::Event(event.clone()),
+        };
+
+        match input {
+            Input::Action(Action::Quit) => self.should_quit = true,
+            Input::Action(Action::Help) => self.show_help = !self.show_help,
+            Input::Action(Action::Close) if self.show_help => self.show_help = false,
+            // The widgets don't get the keys and the clicks while the help is shown.
+            Input::Action(_) | Input::Event(Event::Mouse(_)) if self.show_help => {}
+            input => {
+                if let Err(e) = tx.send(input).await {
+                    error!("Failed to send event to workflow run widget: {}", e);
+                }
+            }
         }
     }
 }
@@ -132,13 +241,43 @@ impl App {
 fn get_github_service() -> Arc<dyn GitHubService> {
     let mut svc = workflows::MockGitHubService::new();
 
-    svc.expect_list_runs().returning(|_| {
+    svc.expect_discover_repos().returning(|sources| {
+        use crate::models::RepositorySource;
+
+        let repos = sources
+            .iter()
+            .filter_map(|s| match s {
+                RepositorySource::Repository(r) => Some(r.clone()),
+                _ => None,
+            })
+            .collect();
+
+        Ok(repos)
+    });
+
+    svc.expect_list_runs().returning(|repo| {
         use fake::Fake;
         use fake::rand::random;
 
         let n = random::<u8>() % 16;
 
-        let workflow_runs = (0..=n).map(|_| fake::Faker.fake()).collect();
+        // The runs are merged per repository, so they must belong to the requested one.
+        let workflow_runs = (0..=n)
+            .map(|_| {
+                let mut run: models::WorkflowRun = fake::Faker.fake();
+                run.owner = repo.owner.clone();
+                run.repo = repo.name.clone();
+                run
+            })
+            .collect();
+
+        Ok(workflow_runs)
+    });
+
+    svc.expect_list_workflow_history().returning(|_, count| {
+        use fake::Fake;
+
+        let workflow_runs = (0..count).map(|_| fake::Faker.fake()).collect();
 
         Ok(workflow_runs)
     });
@@ -154,6 +293,129 @@ fn get_github_service() -> Arc<dyn GitHubService> {
         Ok(workflow_jobs)
     });
 
+    svc.expect_list_job_attempts().returning(|workflow| {
+        use fake::Fake;
+
+        // The same jobs on each attempt, failing until the last one.
+        let jobs: Vec<models::WorkflowJob> = (0..3).map(|_| fake::Faker.fake()).collect();
+        let attempts = (1..=workflow.run_attempt)
+            .flat_map(|attempt| {
+                jobs.iter().map(move |j| models::WorkflowJob {
+                    run_id: workflow.id,
+                    run_attempt: attempt,
+                    head_sha: workflow.head_sha.clone(),
+                    conclusion: match attempt < workflow.run_attempt {
+                        true => models::WorkflowJobConclusion::Failure,
+                        false => models::WorkflowJobConclusion::Success,
+                    },
+                    ..j.clone()
+                })
+            })
+            .collect();
+
+        Ok(attempts)
+    });
+
+    svc.expect_list_check_runs().returning(|_| Ok(vec![]));
+
+    svc.expect_rerun_job().returning(|_, _| Ok(()));
+
+    svc.expect_get_pull_request_title().returning(|_, _| {
+        use fake::Fake;
+        use fake::faker::lorem::en::Sentence;
+
+        Ok(Sentence(3..6).fake())
+    });
+
+    svc.expect_list_runners().returning(|scope| {
+        use fake::Fake;
+        use fake::faker::lorem::en::Word;
+        use fake::rand::random;
+
+        let runners = (0..random::<u8>() % 6)
+            .map(|i| {
+                let online = !random::<u8>().is_multiple_of(4);
+                models::Runner {
+                    id: u64::from(random::<u32>()),
+                    name: format!("{}-runner-{}", Word().fake::<String>(), i),
+                    os: "Linux".to_string(),
+                    scope: scope.clone(),
+                    online,
+                    busy: online && random::<bool>(),
+                    labels: vec!["self-hosted".to_string(), "linux".to_string()],
+                }
+            })
+            .collect();
+
+        Ok(runners)
+    });
+
+    svc.expect_get_workflow_usage().returning(|_| {
+        use fake::Fake;
+        use fake::rand::random;
+
+        // Some workflows report nothing, their usage is estimated.
+        let usage = match random::<u8>().is_multiple_of(4) {
+            true => Default::default(),
+            false => models::RunnerOs::ALL
+                .iter()
+                .filter(|_| random::<bool>())
+                .map(|os| (*os, (0..50 * 3_600_000).fake()))
+                .collect(),
+        };
+
+        Ok(usage)
+    });
+
+    svc.expect_list_artifacts().returning(|_| {
+        use fake::Fake;
+        use fake::faker::lorem::en::Word;
+        use fake::rand::random;
+
+        let artifacts = (0..random::<u8>() % 4)
+            .map(|_| models::Artifact {
+                id: u64::from(random::<u32>()).into(),
+                name: format!("{}-report", Word().fake::<String>()),
+                size_in_bytes: (1024..16 * 1024 * 1024).fake(),
+                expires_at: Some(chrono::Utc::now() + chrono::TimeDelta::days((1..90).fake())),
+                expired: random::<u8>().is_multiple_of(8),
+            })
+            .collect();
+
+        Ok(artifacts)
+    });
+
+    svc.expect_download_artifact()
+        .returning(|_, artifact, path, progress| {
+            use std::io::Write;
+
+            use zip::write::SimpleFileOptions;
+
+            let make_error = || error::ServiceError::from("failed to write the fake artifact");
+
+            // An archive with a JUnit report, the progress jumps to the end.
+            let report = r#"<testsuite name="fake">
+                <testcase classname="fake.api" name="test_login"/>
+                <testcase classname="fake.api" name="test_logout">
+                    <failure message="expected 200, got 500"/>
+                </testcase>
+                <testcase classname="fake.api" name="test_signup"><skipped/></testcase>
+            </testsuite>"#;
+
+            let file = std::fs::File::create(path).or_raise(make_error)?;
+            let mut zip = zip::ZipWriter::new(file);
+            zip.start_file(
+                format!("{}.xml", artifact.name),
+                SimpleFileOptions::default(),
+            )
+            .or_raise(make_error)?;
+            zip.write_all(report.as_bytes()).or_raise(make_error)?;
+            zip.finish().or_raise(make_error)?;
+            progress.send_replace(artifact.size_in_bytes);
+
+            Ok(())
+        });
+
     Arc::new(svc)
 }
 
@@ -1,9 +1,16 @@
 use std::fmt::Display;
 
-use octocrab::models::workflows::{Conclusion, Job, Run, Status};
-use octocrab::models::{JobId, RunId};
+use octocrab::models::workflows::{Conclusion, Job, Status};
+use octocrab::models::{ArtifactId, JobId, RunId, WorkflowId};
 use serde::Deserialize;
 
+use crate::service::responses::{
+    AnnotationResponse,
+    ArtifactResponse,
+    RunResponse,
+    RunnerResponse,
+};
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct Repository {
     pub owner: String,
@@ -11,6 +18,89 @@ pub struct Repository {
     pub branch: Option<String>,
     pub count: Option<u8>,
     pub actor: Option<String>,
+    /// Overrides the global refresh interval, in seconds.
+    pub refresh_interval: Option<u64>,
+}
+
+impl Repository {
+    /// Whether the run belongs to this repository.
+    pub fn owns(&self, run: &WorkflowRun) -> bool {
+        self.owner.eq_ignore_ascii_case(&run.owner) && self.name.eq_ignore_ascii_case(&run.repo)
+    }
+}
+
+/// An entry of the `repos` configuration.
+///
+/// Either a concrete repository, or a source that is expanded into concrete
+/// repositories through the GitHub API.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum RepositorySource {
+    Repository(Repository),
+    Organization(OrganizationSource),
+    Pattern(PatternSource),
+}
+
+/// All the repositories of an organization, optionally filtered by topic.
+#[derive(Debug, Clone, Deserialize)]
+pub struct OrganizationSource {
+    pub org: String,
+    pub topic: Option<String>,
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    pub branch: Option<String>,
+    pub count: Option<u8>,
+    pub actor: Option<String>,
+    /// Overrides the global refresh interval, in seconds.
+    pub refresh_interval: Option<u64>,
+}
+
+/// The repositories of an organization matching a glob pattern, e.g.
+/// `acme/api-*`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PatternSource {
+    pub pattern: String,
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    pub branch: Option<String>,
+    pub count: Option<u8>,
+    pub actor: Option<String>,
+    /// Overrides the global refresh interval, in seconds.
+    pub refresh_interval: Option<u64>,
+}
+
+impl OrganizationSource {
+    pub fn to_repository(&self, name: &str) -> Repository {
+        Repository {
+            owner: self.org.clone(),
+            name: name.to_string(),
+            branch: self.branch.clone(),
+            count: self.count,
+            actor: self.actor.clone(),
+            refresh_interval: self.refresh_interval,
+        }
+    }
+}
+
+impl PatternSource {
+    /// Split the pattern into the owner and the repository name pattern,
+    /// `None` unless it is `<owner>/<name>`.
+    pub fn split(&self) -> Option<(&str, &str)> {
+        self.pattern
+            .split_once('/')
+            .filter(|(owner, name)| !owner.is_empty() && !name.is_empty() && !name.contains('/'))
+    }
+
+    pub fn to_repository(&self, owner: &str, name: &str) -> Repository {
+        Repository {
+            owner: owner.to_string(),
+            name: name.to_string(),
+            branch: self.branch.clone(),
+            count: self.count,
+            actor: self.actor.clone(),
+            refresh_interval: self.refresh_interval,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Default)]
@@ -40,6 +130,18 @@ impl From<&str> for WorkflowRunStatus {
     }
 }
 
+impl WorkflowRunStatus {
+    /// The status as returned by the GitHub API.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Queued => "queued",
+            Self::InProgress => "in_progress",
+            Self::Completed => "completed",
+            Self::Other(c) => c,
+        }
+    }
+}
+
 impl From<&WorkflowRunStatus> for String {
     fn from(v: &WorkflowRunStatus) -> Self {
         match v {
@@ -77,12 +179,29 @@ impl From<&str> for WorkflowRunConclusion {
     }
 }
 
+impl WorkflowRunConclusion {
+    /// Whether the run failed, timed out runs included.
+    pub fn is_failure(&self) -> bool {
+        matches!(self, Self::Failure) || self.as_str() == Some("timed_out")
+    }
+
+    /// The conclusion as returned by the GitHub API, `None` while pending.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::Pending => None,
+            Self::Success => Some("success"),
+            Self::Failure => Some("failure"),
+            Self::Other(c) => Some(c),
+        }
+    }
+}
+
 impl From<&WorkflowRunConclusion> for String {
     fn from(v: &WorkflowRunConclusion) -> Self {
         match v {
-            WorkflowRunConclusion::Pending => "⌛ Pending".to_string(),
-            WorkflowRunConclusion::Success => "✅ Success".to_string(),
-            WorkflowRunConclusion::Failure => "❌ Failure".to_string(),
+            WorkflowRunConclusion::Pending => "Pending".to_string(),
+            WorkflowRunConclusion::Success => "Success".to_string(),
+            WorkflowRunConclusion::Failure => "Failure".to_string(),
             WorkflowRunConclusion::Other(c) => c.to_string(),
         }
     }
@@ -119,6 +238,33 @@ impl From<&Status> for WorkflowJobStatus {
     }
 }
 
+impl WorkflowJobStatus {
+    /// The status as returned by the GitHub API.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Pending => "pending",
+            Self::Queued => "queued",
+            Self::InProgress => "in_progress",
+            Self::Completed => "completed",
+            Self::Failed => "failed",
+            Self::Other(c) => c,
+        }
+    }
+}
+
+impl From<&str> for WorkflowJobStatus {
+    fn from(c: &str) -> Self {
+        match c {
+            "pending" => Self::Pending,
+            "queued" => Self::Queued,
+            "in_progress" => Self::InProgress,
+            "completed" => Self::Completed,
+            "failed" => Self::Failed,
+            _ => Self::Other(c.to_string()),
+        }
+    }
+}
+
 impl From<&WorkflowJobStatus> for String {
     fn from(v: &WorkflowJobStatus) -> Self {
         match v {
@@ -167,16 +313,47 @@ impl From<&Conclusion> for WorkflowJobConclusion {
     }
 }
 
+impl WorkflowJobConclusion {
+    /// The conclusion as returned by the GitHub API.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::ActionRequired => "action_required",
+            Self::Cancelled => "cancelled",
+            Self::Failure => "failure",
+            Self::Neutral => "neutral",
+            Self::Skipped => "skipped",
+            Self::Success => "success",
+            Self::TimedOut => "timed_out",
+            Self::Other(c) => c,
+        }
+    }
+}
+
+impl From<&str> for WorkflowJobConclusion {
+    fn from(c: &str) -> Self {
+        match c {
+            "action_required" => Self::ActionRequired,
+            "cancelled" => Self::Cancelled,
+            "failure" => Self::Failure,
+            "neutral" => Self::Neutral,
+            "skipped" => Self::Skipped,
+            "success" => Self::Success,
+            "timed_out" => Self::TimedOut,
+            _ => Self::Other(c.to_string()),
+        }
+    }
+}
+
 impl From<&WorkflowJobConclusion> for String {
     fn from(v: &WorkflowJobConclusion) -> Self {
         match v {
             WorkflowJobConclusion::ActionRequired => "Action Required".to_string(),
-            WorkflowJobConclusion::Cancelled => "🛑 Cancelled".to_string(),
-            WorkflowJobConclusion::Failure => "❌ Failure".to_string(),
+            WorkflowJobConclusion::Cancelled => "Cancelled".to_string(),
+            WorkflowJobConclusion::Failure => "Failure".to_string(),
             WorkflowJobConclusion::Neutral => "Neutral".to_string(),
-            WorkflowJobConclusion::Skipped => "⏩ Skipped".to_string(),
-            WorkflowJobConclusion::Success => "✅ Success".to_string(),
-            WorkflowJobConclusion::TimedOut => "⏱️ Timed Out".to_string(),
+            WorkflowJobConclusion::Skipped => "Skipped".to_string(),
+            WorkflowJobConclusion::Success => "Success".to_string(),
+            WorkflowJobConclusion::TimedOut => "Timed Out".to_string(),
             WorkflowJobConclusion::Other(c) => c.to_string(),
         }
     }
@@ -185,15 +362,42 @@ impl From<&WorkflowJobConclusion> for String {
 #[derive(Debug, Clone)]
 pub struct WorkflowRun {
     pub id: RunId,
+    pub workflow_id: WorkflowId,
     pub owner: String,
     pub repo: String,
     pub branch: String,
+    /// The SHA of the commit the run is for.
+    pub head_sha: String,
     pub name: String,
     pub commit_message: String,
     pub start_time: chrono::DateTime<chrono::Utc>,
+    pub run_started_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
     pub status: WorkflowRunStatus,
     pub conclusion: WorkflowRunConclusion,
     pub html_url: url::Url,
+    /// The name of the author of the head commit.
+    pub commit_author: String,
+    /// The event that triggered the run, e.g. `push`, `pull_request` or
+    /// `schedule`.
+    pub event: String,
+    /// The user who triggered the first attempt of the run.
+    pub actor: String,
+    /// The user who triggered the latest attempt of the run, e.g. a re-run.
+    pub triggering_actor: String,
+    pub run_number: u64,
+    pub run_attempt: u64,
+    pub pull_request: Option<PullRequest>,
+    /// The check suite of the run, whose check runs are the jobs.
+    pub check_suite_id: Option<u64>,
+}
+
+/// The pull request a run is for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PullRequest {
+    pub number: u64,
+    /// Only known from the run for the pull request events.
+    pub title: Option<String>,
 }
 
 impl Display for WorkflowRun {
@@ -213,8 +417,50 @@ impl Display for WorkflowRun {
     }
 }
 
-impl From<&Run> for WorkflowRun {
-    fn from(r: &Run) -> Self {
+impl WorkflowRun {
+    /// The total duration of a completed run, or the elapsed time of a run
+    /// that is still going.
+    pub fn duration(&self, now: chrono::DateTime<chrono::Utc>) -> chrono::TimeDelta {
+        let end = match self.status {
+            WorkflowRunStatus::Completed => self.updated_at,
+            _ => now,
+        };
+
+        (end - self.run_started_at).max(chrono::TimeDelta::zero())
+    }
+
+    pub fn is_completed(&self) -> bool {
+        matches!(self.status, WorkflowRunStatus::Completed)
+    }
+
+    /// The run on one line, to paste in a chat or an issue, e.g.
+    /// `owner/repo CI on main (1a2b3c4): failure https://github.com/...`.
+    pub fn summary(&self) -> String {
+        let outcome = match self.is_completed() {
+            true => self.conclusion.to_string(),
+            false => self.status.to_string(),
+        };
+
+        format!(
+            "{}/{} {} on {} ({}): {} {}",
+            self.owner,
+            self.repo,
+            self.name,
+            self.branch,
+            self.short_sha(),
+            outcome,
+            self.html_url
+        )
+    }
+
+    /// The abbreviated SHA of the commit, as displayed by GitHub.
+    pub fn short_sha(&self) -> &str {
+        self.head_sha.get(..7).unwrap_or(&self.head_sha)
+    }
+}
+
+impl From<&RunResponse> for WorkflowRun {
+    fn from(r: &RunResponse) -> Self {
         let conclusion = r
             .conclusion
             .as_ref()
@@ -229,15 +475,44 @@ impl From<&Run> for WorkflowRun {
 
         Self {
             id: r.id,
+            workflow_id: r.workflow_id,
             owner,
             repo: r.repository.name.clone(),
-            branch: r.head_branch.clone(),
-            name: r.name.clone(),
-            commit_message: r.head_commit.message.clone(),
+            branch: r.head_branch.clone().unwrap_or_default(),
+            head_sha: r.head_sha.clone(),
+            name: r.name.clone().unwrap_or_default(),
+            commit_message: r
+                .head_commit
+                .as_ref()
+                .map_or(String::new(), |c| c.message.clone()),
             start_time: r.created_at,
-            status: WorkflowRunStatus::from(r.status.as_str()),
+            run_started_at: r.run_started_at.unwrap_or(r.created_at),
+            updated_at: r.updated_at,
+            status: WorkflowRunStatus::from(r.status.as_deref().unwrap_or_default()),
             conclusion,
             html_url: r.html_url.clone(),
+            commit_author: r
+                .head_commit
+                .as_ref()
+                .and_then(|c| c.author.as_ref())
+                .map_or(String::new(), |a| a.name.clone()),
+            event: r.event.clone(),
+            actor: r.actor.as_ref().map_or(String::new(), |a| a.login.clone()),
+            triggering_actor: r
+                .triggering_actor
+                .as_ref()
+                .or(r.actor.as_ref())
+                .map_or(String::new(), |a| a.login.clone()),
+            run_number: r.run_number,
+            run_attempt: r.run_attempt.unwrap_or(1),
+            check_suite_id: r.check_suite_id,
+            pull_request: r.pull_requests.first().map(|pr| PullRequest {
+                number: pr.number,
+                title: match r.event.as_str() {
+                    "pull_request" | "pull_request_target" => r.display_title.clone(),
+                    _ => None,
+                },
+            }),
         }
     }
 }
@@ -245,12 +520,20 @@ impl From<&Run> for WorkflowRun {
 #[derive(Debug, Clone)]
 pub struct WorkflowJob {
     pub id: JobId,
+    pub run_id: RunId,
     pub name: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
     pub started_at: chrono::DateTime<chrono::Utc>,
     pub completed_at: Option<chrono::DateTime<chrono::Utc>>,
     pub status: WorkflowJobStatus,
     pub conclusion: WorkflowJobConclusion,
     pub html_url: url::Url,
+    /// The attempt of the run the job is part of, each attempt has its own
+    /// jobs.
+    pub run_attempt: u64,
+    pub head_sha: String,
+    /// The labels of the runners the job can run on.
+    pub labels: Vec<String>,
 }
 
 impl Display for WorkflowJob {
@@ -263,6 +546,38 @@ impl Display for WorkflowJob {
     }
 }
 
+impl WorkflowJob {
+    /// The time the job waited for a runner.
+    pub fn queue_time(&self) -> chrono::TimeDelta {
+        (self.started_at - self.created_at).max(chrono::TimeDelta::zero())
+    }
+
+    /// The total duration of a completed job, or the elapsed time of a job
+    /// that is still going.
+    pub fn duration(&self, now: chrono::DateTime<chrono::Utc>) -> chrono::TimeDelta {
+        (self.completed_at.unwrap_or(now) - self.started_at).max(chrono::TimeDelta::zero())
+    }
+
+    /// Whether the job waits for a self-hosted runner rather than a runner
+    /// hosted by GitHub.
+    pub fn is_self_hosted(&self) -> bool {
+        self.labels
+            .iter()
+            .any(|l| l.eq_ignore_ascii_case("self-hosted"))
+    }
+
+    /// The OS of the runner hosted by GitHub the job runs on, `None` for the
+    /// self-hosted runners and the labels that don't name an OS, e.g. the
+    /// larger runners.
+    pub fn runner_os(&self) -> Option<RunnerOs> {
+        if self.is_self_hosted() {
+            return None;
+        }
+
+        self.labels.iter().find_map(|l| RunnerOs::from_label(l))
+    }
+}
+
 impl From<Job> for WorkflowJob {
     fn from(j: Job) -> Self {
         let conclusion = j.conclusion.as_ref().map_or(
@@ -272,12 +587,228 @@ impl From<Job> for WorkflowJob {
 
         Self {
             id: j.id,
+            run_id: j.run_id,
             name: j.name.clone(),
+            created_at: j.created_at,
             started_at: j.started_at,
             completed_at: j.completed_at,
             status: (&j.status).into(),
             conclusion,
             html_url: j.html_url.clone(),
+            run_attempt: u64::from(j.run_attempt),
+            head_sha: j.head_sha.clone(),
+            labels: j.labels.clone(),
+        }
+    }
+}
+
+/// The check run of a job: the summary and the annotations it reported, e.g.
+/// the compile errors and the test failures.
+#[derive(Debug, Clone)]
+pub struct CheckRun {
+    /// The check run of a job has the id of the job.
+    pub job_id: JobId,
+    pub name: String,
+    pub title: Option<String>,
+    /// Markdown.
+    pub summary: Option<String>,
+    pub annotations: Vec<Annotation>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Annotation {
+    pub path: String,
+    pub start_line: u64,
+    pub end_line: u64,
+    pub level: AnnotationLevel,
+    pub title: Option<String>,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AnnotationLevel {
+    Failure,
+    Warning,
+    Notice,
+}
+
+impl From<&str> for AnnotationLevel {
+    fn from(value: &str) -> Self {
+        match value {
+            "failure" => Self::Failure,
+            "warning" => Self::Warning,
+            _ => Self::Notice,
+        }
+    }
+}
+
+impl From<&AnnotationResponse> for Annotation {
+    fn from(a: &AnnotationResponse) -> Self {
+        Self {
+            path: a.path.clone(),
+            start_line: a.start_line,
+            end_line: a.end_line,
+            level: AnnotationLevel::from(a.annotation_level.as_deref().unwrap_or_default()),
+            title: a.title.clone().filter(|t| !t.is_empty()),
+            message: a.message.clone(),
+        }
+    }
+}
+
+/// A file uploaded by a run, e.g. a test report or a binary.
+#[derive(Debug, Clone)]
+pub struct Artifact {
+    pub id: ArtifactId,
+    pub name: String,
+    /// The size of the zip archive.
+    pub size_in_bytes: u64,
+    pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// An expired artifact can't be downloaded anymore.
+    pub expired: bool,
+}
+
+impl From<&ArtifactResponse> for Artifact {
+    fn from(a: &ArtifactResponse) -> Self {
+        Self {
+            id: a.id,
+            name: a.name.clone(),
+            size_in_bytes: a.size_in_bytes,
+            expires_at: a.expires_at,
+            expired: a.expired,
         }
     }
 }
+
+/// Where self-hosted runners are registered.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum RunnerScope {
+    Organization(String),
+    Repository { owner: String, name: String },
+}
+
+impl Display for RunnerScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Organization(org) => write!(f, "{}", org),
+            Self::Repository { owner, name } => write!(f, "{}/{}", owner, name),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Runner {
+    pub id: u64,
+    pub name: String,
+    pub os: String,
+    pub scope: RunnerScope,
+    pub online: bool,
+    /// Whether the runner is running a job.
+    pub busy: bool,
+    pub labels: Vec<String>,
+}
+
+impl Runner {
+    /// Whether the runner has all the labels a job asks for.
+    pub fn can_run(&self, job: &WorkflowJob) -> bool {
+        job.labels
+            .iter()
+            .all(|wanted| self.labels.iter().any(|l| l.eq_ignore_ascii_case(wanted)))
+    }
+
+    pub fn from_response(r: &RunnerResponse, scope: &RunnerScope) -> Self {
+        Self {
+            id: r.id,
+            name: r.name.clone(),
+            os: r.os.clone(),
+            scope: scope.clone(),
+            online: r.status == "online",
+            busy: r.busy,
+            labels: r.labels.iter().map(|l| l.name.clone()).collect(),
+        }
+    }
+}
+
+/// The OS of the runners hosted by GitHub, their minutes are billed at
+/// different rates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RunnerOs {
+    Linux,
+    Windows,
+    MacOs,
+}
+
+impl Display for RunnerOs {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Linux => "Linux",
+            Self::Windows => "Windows",
+            Self::MacOs => "macOS",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl RunnerOs {
+    pub const ALL: [RunnerOs; 3] = [RunnerOs::Linux, RunnerOs::Windows, RunnerOs::MacOs];
+
+    /// How many included minutes a minute on this OS uses up.
+    pub fn multiplier(&self) -> u64 {
+        match self {
+            Self::Linux => 1,
+            Self::Windows => 2,
+            Self::MacOs => 10,
+        }
+    }
+
+    /// The OS of a runner label, e.g. `ubuntu-latest` or `macos-14`.
+    pub fn from_label(label: &str) -> Option<Self> {
+        let label = label.to_ascii_lowercase();
+
+        if label.starts_with("ubuntu") || label == "linux" {
+            Some(Self::Linux)
+        } else if label.starts_with("windows") {
+            Some(Self::Windows)
+        } else if label.starts_with("macos") {
+            Some(Self::MacOs)
+        } else {
+            None
+        }
+    }
+
+    /// The OS of a key of the workflow timing endpoint, e.g. `UBUNTU`.
+    pub fn from_billing_key(key: &str) -> Option<Self> {
+        match key {
+            "UBUNTU" => Some(Self::Linux),
+            "WINDOWS" => Some(Self::Windows),
+            "MACOS" => Some(Self::MacOs),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pattern(pattern: &str) -> PatternSource {
+        PatternSource {
+            pattern: pattern.to_string(),
+            exclude: vec![],
+            branch: None,
+            count: None,
+            actor: None,
+            refresh_interval: None,
+        }
+    }
+
+    #[test]
+    fn split_patterns() {
+        assert_eq!(pattern("acme/api-*").split(), Some(("acme", "api-*")));
+        assert_eq!(pattern("acme/*").split(), Some(("acme", "*")));
+
+        assert_eq!(pattern("acme").split(), None);
+        assert_eq!(pattern("acme/").split(), None);
+        assert_eq!(pattern("/api-*").split(), None);
+        assert_eq!(pattern("acme/api/*").split(), None);
+    }
+}
@@ -1 +1,3 @@
+pub mod repositories;
+pub mod responses;
 pub mod workflows;
@@ -0,0 +1,171 @@
+use std::sync::Arc;
+
+use exn::{Result, ResultExt, bail};
+use glob::Pattern;
+use log::{debug, error};
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+use crate::error::ServiceError;
+use crate::models::{OrganizationSource, PatternSource, Repository};
+
+/// The number of repositories checked for workflows at once, to stay below
+/// the secondary rate limits of GitHub on the large organizations.
+const MAX_CONCURRENT_CHECKS: usize = 8;
+
+/// Expand an organization source into the repositories it covers.
+///
+/// The repositories that can't be checked for workflows are skipped until the
+/// next discovery.
+pub async fn expand_organization(
+    source: OrganizationSource,
+) -> Result<Vec<Repository>, ServiceError> {
+    let make_error =
+        || ServiceError::from(format!("Error expanding the organization {}", source.org));
+
+    let excludes = compile_patterns(&source.exclude)?;
+
+    let candidates = list_org_repos(&source.org)
+        .await
+        .or_raise(make_error)?
+        .into_iter()
+        .filter(|r| match &source.topic {
+            Some(topic) => r.topics.as_ref().is_some_and(|t| t.contains(topic)),
+            None => true,
+        })
+        .filter(|r| !is_excluded(&excludes, &r.name))
+        .map(|r| source.to_repository(&r.name))
+        .collect();
+
+    Ok(with_actions_activity(candidates).await)
+}
+
+/// Expand a pattern source into the repositories matching it.
+///
+/// An invalid pattern fails the discovery, so that it is reported rather than
+/// silently matching nothing.
+pub async fn expand_pattern(source: PatternSource) -> Result<Vec<Repository>, ServiceError> {
+    let make_error =
+        || ServiceError::from(format!("Error expanding the pattern {}", source.pattern));
+
+    let Some((owner, name_pattern)) = source.split() else {
+        bail!(ServiceError::from(format!(
+            "Invalid repository pattern, expected <owner>/<name>: {}",
+            source.pattern
+        )));
+    };
+
+    let pattern = Pattern::new(name_pattern).or_raise(make_error)?;
+    let excludes = compile_patterns(&source.exclude)?;
+
+    let candidates = list_org_repos(owner)
+        .await
+        .or_raise(make_error)?
+        .into_iter()
+        .filter(|r| pattern.matches(&r.name))
+        .filter(|r| !is_excluded(&excludes, &r.name))
+        .map(|r| source.to_repository(owner, &r.name))
+        .collect();
+
+    Ok(with_actions_activity(candidates).await)
+}
+
+/// List the non-archived repositories of an organization.
+async fn list_org_repos(org: &str) -> octocrab::Result<Vec<octocrab::models::Repository>> {
+    let crab = octocrab::instance();
+    let page = crab.orgs(org).list_repos().per_page(100).send().await?;
+    let repos = crab.all_pages(page).await?;
+
+    Ok(repos
+        .into_iter()
+        .filter(|r| !r.archived.unwrap_or(false))
+        .collect())
+}
+
+/// Keep only the repositories that have at least one workflow, and the ones
+/// that can't be checked are skipped.
+async fn with_actions_activity(repos: Vec<Repository>) -> Vec<Repository> {
+    let permits = Arc::new(Semaphore::new(MAX_CONCURRENT_CHECKS));
+    let mut set = JoinSet::new();
+
+    repos.into_iter().for_each(|repo| {
+        let permits = permits.clone();
+        set.spawn(async move {
+            let _permit = permits.acquire_owned().await;
+            let has_workflows = has_workflows(&repo).await;
+            (repo, has_workflows)
+        });
+    });
+
+    let mut active = vec![];
+
+    while let Some(res) = set.join_next().await {
+        match res {
+            Ok((repo, Ok(true))) => active.push(repo),
+            Ok((repo, Ok(false))) => {
+                debug!(
+                    "Skipping {}/{}: no actions workflows",
+                    repo.owner, repo.name
+                );
+            }
+            Ok((repo, Err(e))) => {
+                error!(
+                    "Skipping {}/{}: unable to get its workflows: {:?}",
+                    repo.owner, repo.name, e
+                );
+            }
+            Err(e) => error!("Failed to check the workflows of a repository: {:?}", e),
+        }
+    }
+
+    active.sort_by(|a, b| (&a.owner, &a.name).cmp(&(&b.owner, &b.name)));
+
+    active
+}
+
+async fn has_workflows(repo: &Repository) -> octocrab::Result<bool> {
+    let page = octocrab::instance()
+        .workflows(&repo.owner, &repo.name)
+        .list()
+        .per_page(1)
+        .send()
+        .await?;
+
+    Ok(!page.items.is_empty())
+}
+
+fn compile_patterns(patterns: &[String]) -> Result<Vec<Pattern>, ServiceError> {
+    patterns
+        .iter()
+        .map(|p| {
+            Pattern::new(p)
+                .or_raise(|| ServiceError::from(format!("Invalid exclude pattern {}", p)))
+        })
+        .collect()
+}
+
+fn is_excluded(excludes: &[Pattern], name: &str) -> bool {
+    excludes.iter().any(|p| p.matches(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exclude_repositories() {
+        let excludes =
+            compile_patterns(&["legacy-*".to_string(), "api-?-deprecated".to_string()]).unwrap();
+
+        assert!(is_excluded(&excludes, "legacy-service"));
+        assert!(is_excluded(&excludes, "api-v-deprecated"));
+        assert!(!is_excluded(&excludes, "api-v2-deprecated"));
+        assert!(!is_excluded(&excludes, "service"));
+        assert!(!is_excluded(&[], "legacy-service"));
+    }
+
+    #[test]
+    fn reject_invalid_exclude_patterns() {
+        assert!(compile_patterns(&["legacy-[".to_string()]).is_err());
+    }
+}
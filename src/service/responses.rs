@@ -0,0 +1,176 @@
+use std::collections::HashMap;
+
+use octocrab::models::{ArtifactId, JobId, RunId, WorkflowId};
+use serde::{Deserialize, Serialize};
+
+/// A workflow run, as returned by the GitHub API.
+///
+/// Octocrab's `Run` model doesn't expose all the fields we need, so the runs
+/// are deserialized into this instead.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RunResponse {
+    pub id: RunId,
+    pub workflow_id: WorkflowId,
+    pub name: Option<String>,
+    /// The title of the pull request for the pull request events, the commit
+    /// title otherwise.
+    pub display_title: Option<String>,
+    pub head_branch: Option<String>,
+    pub head_sha: String,
+    pub status: Option<String>,
+    pub conclusion: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+    pub run_started_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub html_url: url::Url,
+    pub head_commit: Option<HeadCommitResponse>,
+    pub repository: RepositoryResponse,
+    pub event: String,
+    pub run_number: u64,
+    pub run_attempt: Option<u64>,
+    pub check_suite_id: Option<u64>,
+    pub actor: Option<UserResponse>,
+    pub triggering_actor: Option<UserResponse>,
+    /// Empty for the pull requests from forks.
+    #[serde(default)]
+    pub pull_requests: Vec<PullRequestResponse>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct HeadCommitResponse {
+    pub message: String,
+    pub author: Option<CommitAuthorResponse>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CommitAuthorResponse {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct UserResponse {
+    pub login: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PullRequestResponse {
+    pub number: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RepositoryResponse {
+    pub name: String,
+    pub owner: Option<OwnerResponse>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct OwnerResponse {
+    pub login: String,
+}
+
+/// The check runs of a check suite.
+#[derive(Debug, Clone, Deserialize)]
+pub struct CheckRunsResponse {
+    pub check_runs: Vec<CheckRunResponse>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CheckRunResponse {
+    pub id: JobId,
+    pub name: String,
+    pub output: CheckRunOutputResponse,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CheckRunOutputResponse {
+    pub title: Option<String>,
+    pub summary: Option<String>,
+    pub annotations_count: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AnnotationResponse {
+    pub path: String,
+    pub start_line: u64,
+    pub end_line: u64,
+    pub annotation_level: Option<String>,
+    pub title: Option<String>,
+    pub message: String,
+}
+
+/// Query parameters of the list workflow runs endpoints.
+#[derive(Debug, Default, Serialize)]
+pub struct ListRunsParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actor: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    pub per_page: u8,
+}
+
+/// The artifacts of a run.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ArtifactsResponse {
+    pub artifacts: Vec<ArtifactResponse>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ArtifactResponse {
+    pub id: ArtifactId,
+    pub name: String,
+    pub size_in_bytes: u64,
+    pub expired: bool,
+    pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// The self-hosted runners of an organization or a repository.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RunnersResponse {
+    pub runners: Vec<RunnerResponse>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RunnerResponse {
+    pub id: u64,
+    pub name: String,
+    pub os: String,
+    /// `online` or `offline`.
+    pub status: String,
+    pub busy: bool,
+    pub labels: Vec<RunnerLabelResponse>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RunnerLabelResponse {
+    pub name: String,
+}
+
+/// The billable time of a workflow in the current billing cycle, per OS.
+#[derive(Debug, Clone, Deserialize)]
+pub struct WorkflowTimingResponse {
+    /// Keyed by `UBUNTU`, `WINDOWS` and `MACOS`.
+    pub billable: HashMap<String, BillableTimeResponse>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BillableTimeResponse {
+    pub total_ms: u64,
+}
+
+/// Query parameters of the endpoints that only take a page.
+#[derive(Debug, Serialize)]
+pub struct PageParams {
+    pub per_page: u8,
+    pub page: u32,
+}
+
+impl Default for PageParams {
+    fn default() -> Self {
+        Self {
+            per_page: 100,
+            page: 1,
+        }
+    }
+}
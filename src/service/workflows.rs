@@ -1,21 +1,110 @@
+use std::collections::{BTreeMap, HashSet};
 use std::fmt::{Debug, Formatter};
+use std::path::Path;
 
 use async_trait::async_trait;
 use exn::{Result, ResultExt};
+use http_body_util::BodyExt;
 use log::error;
 #[cfg(any(test, feature = "mocks"))]
 use mockall::automock;
+use octocrab::Page;
+use octocrab::params::workflows::Filter;
+use serde::de::DeserializeOwned;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::watch;
 use tokio::task::JoinSet;
 
 use crate::error::ServiceError;
-use crate::models::{Repository, WorkflowJob, WorkflowRun};
+use crate::models::{
+    Artifact,
+    CheckRun,
+    Repository,
+    RepositorySource,
+    Runner,
+    RunnerOs,
+    RunnerScope,
+    WorkflowJob,
+    WorkflowRun,
+};
+use crate::service::repositories;
+use crate::service::responses::{
+    AnnotationResponse,
+    ArtifactsResponse,
+    CheckRunsResponse,
+    ListRunsParams,
+    PageParams,
+    RunResponse,
+    RunnersResponse,
+    WorkflowTimingResponse,
+};
 
 #[cfg_attr(any(test, feature = "mocks"), automock)]
 #[async_trait]
 pub trait GitHubService: Debug + Send + Sync {
-    async fn list_runs(&self, repos: &[Repository]) -> Result<Vec<WorkflowRun>, ServiceError>;
+    async fn discover_repos(
+        &self,
+        sources: &[RepositorySource],
+    ) -> Result<Vec<Repository>, ServiceError>;
+
+    /// List the latest runs of a repository.
+    async fn list_runs(&self, repo: &Repository) -> Result<Vec<WorkflowRun>, ServiceError>;
 
     async fn list_jobs(&self, workflow: &WorkflowRun) -> Result<Vec<WorkflowJob>, ServiceError>;
+
+    /// List the jobs of all the attempts of `workflow`, from the first.
+    async fn list_job_attempts(
+        &self,
+        workflow: &WorkflowRun,
+    ) -> Result<Vec<WorkflowJob>, ServiceError>;
+
+    /// List the last `count` completed runs of the same workflow as `workflow`.
+    async fn list_workflow_history(
+        &self,
+        workflow: &WorkflowRun,
+        count: u8,
+    ) -> Result<Vec<WorkflowRun>, ServiceError>;
+
+    /// Re-run a job of `workflow`, and the jobs that depend on it.
+    async fn rerun_job(
+        &self,
+        workflow: &WorkflowRun,
+        job: &WorkflowJob,
+    ) -> Result<(), ServiceError>;
+
+    /// The check runs of the jobs of `workflow`, with their annotations.
+    async fn list_check_runs(&self, workflow: &WorkflowRun) -> Result<Vec<CheckRun>, ServiceError>;
+
+    /// The title of a pull request of the repository of `workflow`.
+    async fn get_pull_request_title(
+        &self,
+        workflow: &WorkflowRun,
+        number: u64,
+    ) -> Result<String, ServiceError>;
+
+    /// The self-hosted runners registered in `scope`.
+    async fn list_runners(&self, scope: &RunnerScope) -> Result<Vec<Runner>, ServiceError>;
+
+    /// The billable milliseconds of the workflow of `workflow` in the current
+    /// billing cycle, per OS. The runners hosted by GitHub are only billed
+    /// for the private repositories.
+    async fn get_workflow_usage(
+        &self,
+        workflow: &WorkflowRun,
+    ) -> Result<BTreeMap<RunnerOs, u64>, ServiceError>;
+
+    /// The artifacts uploaded by `workflow`.
+    async fn list_artifacts(&self, workflow: &WorkflowRun) -> Result<Vec<Artifact>, ServiceError>;
+
+    /// Download the zip archive of an artifact to `path`, sending the number
+    /// of bytes received so far to `progress`.
+    async fn download_artifact(
+        &self,
+        workflow: &WorkflowRun,
+        artifact: &Artifact,
+        path: &Path,
+        progress: &watch::Sender<u64>,
+    ) -> Result<(), ServiceError>;
 }
 
 pub struct Service {}
@@ -28,34 +117,62 @@ impl Debug for Service {
 
 #[async_trait]
 impl GitHubService for Service {
-    async fn list_runs(&self, repos: &[Repository]) -> Result<Vec<WorkflowRun>, ServiceError> {
-        let make_error = || ServiceError::from("Error getting workflow runs");
+    async fn discover_repos(
+        &self,
+        sources: &[RepositorySource],
+    ) -> Result<Vec<Repository>, ServiceError> {
+        let make_error = || ServiceError::from("Error discovering repositories");
 
+        let mut repos = vec![];
         let mut set = JoinSet::new();
 
-        repos.iter().for_each(|repo| {
-            set.spawn(list_runs_for_repo(repo.clone()));
+        sources.iter().for_each(|source| match source {
+            RepositorySource::Repository(repo) => repos.push(repo.clone()),
+            RepositorySource::Organization(org) => {
+                set.spawn(repositories::expand_organization(org.clone()));
+            }
+            RepositorySource::Pattern(pattern) => {
+                set.spawn(repositories::expand_pattern(pattern.clone()));
+            }
         });
 
-        let mut workflows = vec![];
-
         while let Some(res) = set.join_next().await {
-            let octo_res = res.or_raise(make_error)?;
-
-            let repo_workflows = match octo_res {
-                Ok(wf) => wf,
-                Err(e) => {
-                    error!("Failed to get workflow runs for repo: {:?}", e);
-                    continue;
-                }
-            };
+            let expanded = res.or_raise(make_error)?;
 
-            workflows.extend(repo_workflows);
+            // Fail the whole discovery, so that the previously discovered repositories
+            // are kept.
+            repos.extend(expanded.or_raise(make_error)?);
         }
 
-        workflows.sort_by(|a, b| Ord::cmp(&a.start_time, &b.start_time).reverse());
+        // A repository can be matched by several sources, the first one wins.
+        let mut seen = HashSet::new();
+        repos.retain(|r| seen.insert((r.owner.clone(), r.name.clone())));
 
-        Ok(workflows)
+        Ok(repos)
+    }
+
+    async fn list_runs(&self, repo: &Repository) -> Result<Vec<WorkflowRun>, ServiceError> {
+        let make_error = || {
+            ServiceError::from(format!(
+                "Error getting workflow runs for {}/{}",
+                repo.owner, repo.name
+            ))
+        };
+
+        let route = format!("/repos/{}/{}/actions/runs", repo.owner, repo.name);
+        let params = ListRunsParams {
+            branch: repo.branch.clone(),
+            actor: repo.actor.clone(),
+            per_page: repo.count.unwrap_or(1),
+            ..Default::default()
+        };
+
+        let runs: Page<RunResponse> = octocrab::instance()
+            .get(route, Some(&params))
+            .await
+            .or_raise(make_error)?;
+
+        Ok(runs.items.iter().map(Into::into).collect())
     }
 
     async fn list_jobs(&self, workflow: &WorkflowRun) -> Result<Vec<WorkflowJob>, ServiceError> {
@@ -70,17 +187,274 @@ impl GitHubService for Service {
 
         Ok(jobs.into_iter().map(Into::into).collect())
     }
+
+    async fn list_job_attempts(
+        &self,
+        workflow: &WorkflowRun,
+    ) -> Result<Vec<WorkflowJob>, ServiceError> {
+        let make_error = || ServiceError::from("Error getting the attempts of the workflow");
+
+        let crab = octocrab::instance();
+        let page = crab
+            .workflows(&workflow.owner, &workflow.repo)
+            .list_jobs(workflow.id)
+            .filter(Filter::All)
+            .per_page(100)
+            .send()
+            .await
+            .or_raise(make_error)?;
+        let jobs = crab.all_pages(page).await.or_raise(make_error)?;
+
+        let mut jobs: Vec<WorkflowJob> = jobs.into_iter().map(Into::into).collect();
+        jobs.sort_by_key(|j| (j.run_attempt, j.started_at));
+
+        Ok(jobs)
+    }
+
+    async fn list_workflow_history(
+        &self,
+        workflow: &WorkflowRun,
+        count: u8,
+    ) -> Result<Vec<WorkflowRun>, ServiceError> {
+        let make_error = || ServiceError::from("Error getting workflow history");
+
+        let route = format!(
+            "/repos/{}/{}/actions/workflows/{}/runs",
+            workflow.owner, workflow.repo, workflow.workflow_id
+        );
+        let params = ListRunsParams {
+            status: Some("completed".to_string()),
+            per_page: count,
+            ..Default::default()
+        };
+
+        let runs: Page<RunResponse> = octocrab::instance()
+            .get(route, Some(&params))
+            .await
+            .or_raise(make_error)?;
+
+        Ok(runs.items.iter().map(Into::into).collect())
+    }
+
+    async fn rerun_job(
+        &self,
+        workflow: &WorkflowRun,
+        job: &WorkflowJob,
+    ) -> Result<(), ServiceError> {
+        let make_error = || ServiceError::from(format!("Error re-running job {}", job.name));
+
+        let route = format!(
+            "/repos/{}/{}/actions/jobs/{}/rerun",
+            workflow.owner, workflow.repo, job.id
+        );
+
+        // The response has no body.
+        let response = octocrab::instance()
+            ._post(route, None::<&()>)
+            .await
+            .or_raise(make_error)?;
+        octocrab::map_github_error(response)
+            .await
+            .or_raise(make_error)?;
+
+        Ok(())
+    }
+
+    async fn list_check_runs(&self, workflow: &WorkflowRun) -> Result<Vec<CheckRun>, ServiceError> {
+        let make_error = || ServiceError::from("Error getting the check runs of the workflow");
+
+        let Some(check_suite_id) = workflow.check_suite_id else {
+            return Ok(vec![]);
+        };
+
+        let route = format!(
+            "/repos/{}/{}/check-suites/{}/check-runs",
+            workflow.owner, workflow.repo, check_suite_id
+        );
+        let check_runs = get_all_pages(&route, |r: CheckRunsResponse| r.check_runs)
+            .await
+            .or_raise(make_error)?;
+
+        let mut set = JoinSet::new();
+
+        for check_run in check_runs {
+            let route = format!(
+                "/repos/{}/{}/check-runs/{}/annotations",
+                workflow.owner, workflow.repo, check_run.id
+            );
+
+            set.spawn(async move {
+                // Only the check runs with annotations need another request. The
+                // check run is kept without its annotations if they can't be
+                // fetched.
+                let annotations: Vec<AnnotationResponse> = match check_run.output.annotations_count
+                {
+                    0 => vec![],
+                    _ => get_all_pages(&route, |a: Vec<AnnotationResponse>| a)
+                        .await
+                        .unwrap_or_else(|err| {
+                            error!(
+                                "Failed to get the annotations of {}: {:?}",
+                                check_run.name, err
+                            );
+                            vec![]
+                        }),
+                };
+
+                CheckRun {
+                    job_id: check_run.id,
+                    name: check_run.name,
+                    title: check_run.output.title,
+                    summary: check_run.output.summary,
+                    annotations: annotations.iter().map(Into::into).collect(),
+                }
+            });
+        }
+
+        let mut check_runs = vec![];
+
+        while let Some(res) = set.join_next().await {
+            check_runs.push(res.or_raise(make_error)?);
+        }
+        check_runs.sort_by_key(|c| c.job_id);
+
+        Ok(check_runs)
+    }
+
+    async fn get_pull_request_title(
+        &self,
+        workflow: &WorkflowRun,
+        number: u64,
+    ) -> Result<String, ServiceError> {
+        let make_error = || ServiceError::from(format!("Error getting pull request #{}", number));
+
+        let pull_request = octocrab::instance()
+            .pulls(&workflow.owner, &workflow.repo)
+            .get(number)
+            .await
+            .or_raise(make_error)?;
+
+        Ok(pull_request.title.unwrap_or_default())
+    }
+
+    async fn list_runners(&self, scope: &RunnerScope) -> Result<Vec<Runner>, ServiceError> {
+        let make_error = || ServiceError::from(format!("Error getting the runners of {}", scope));
+
+        let route = match scope {
+            RunnerScope::Organization(org) => format!("/orgs/{}/actions/runners", org),
+            RunnerScope::Repository { owner, name } => {
+                format!("/repos/{}/{}/actions/runners", owner, name)
+            }
+        };
+        let response: RunnersResponse = octocrab::instance()
+            .get(route, Some(&PageParams::default()))
+            .await
+            .or_raise(make_error)?;
+
+        Ok(response
+            .runners
+            .iter()
+            .map(|r| Runner::from_response(r, scope))
+            .collect())
+    }
+
+    async fn get_workflow_usage(
+        &self,
+        workflow: &WorkflowRun,
+    ) -> Result<BTreeMap<RunnerOs, u64>, ServiceError> {
+        let make_error = || ServiceError::from("Error getting the usage of the workflow");
+
+        let route = format!(
+            "/repos/{}/{}/actions/workflows/{}/timing",
+            workflow.owner, workflow.repo, workflow.workflow_id
+        );
+        let response: WorkflowTimingResponse = octocrab::instance()
+            .get(route, None::<&()>)
+            .await
+            .or_raise(make_error)?;
+
+        Ok(response
+            .billable
+            .iter()
+            .filter_map(|(key, time)| Some((RunnerOs::from_billing_key(key)?, time.total_ms)))
+            .collect())
+    }
+
+    async fn list_artifacts(&self, workflow: &WorkflowRun) -> Result<Vec<Artifact>, ServiceError> {
+        let make_error = || ServiceError::from("Error getting the artifacts of the workflow");
+
+        let route = format!(
+            "/repos/{}/{}/actions/runs/{}/artifacts",
+            workflow.owner, workflow.repo, workflow.id
+        );
+        let response: ArtifactsResponse = octocrab::instance()
+            .get(route, Some(&PageParams::default()))
+            .await
+            .or_raise(make_error)?;
+
+        Ok(response.artifacts.iter().map(Into::into).collect())
+    }
+
+    async fn download_artifact(
+        &self,
+        workflow: &WorkflowRun,
+        artifact: &Artifact,
+        path: &Path,
+        progress: &watch::Sender<u64>,
+    ) -> Result<(), ServiceError> {
+        let make_error =
+            || ServiceError::from(format!("Error downloading artifact {}", artifact.name));
+
+        let route = format!(
+            "/repos/{}/{}/actions/artifacts/{}/zip",
+            workflow.owner, workflow.repo, artifact.id
+        );
+
+        // The API redirects to the storage serving the archive.
+        let crab = octocrab::instance();
+        let response = crab._get(route).await.or_raise(make_error)?;
+        let response = crab
+            .follow_location_to_data(response)
+            .await
+            .or_raise(make_error)?;
+        let response = octocrab::map_github_error(response)
+            .await
+            .or_raise(make_error)?;
+
+        let mut file = tokio::fs::File::create(path).await.or_raise(make_error)?;
+        let mut body = response.into_body();
+        let mut received = 0;
+
+        while let Some(frame) = body.frame().await {
+            let frame = frame.or_raise(make_error)?;
+            if let Some(data) = frame.data_ref() {
+                file.write_all(data).await.or_raise(make_error)?;
+                received += data.len() as u64;
+                progress.send_replace(received);
+            }
+        }
+
+        file.flush().await.or_raise(make_error)
+    }
 }
 
-async fn list_runs_for_repo(repo: Repository) -> octocrab::Result<Vec<WorkflowRun>> {
-    let workflows = octocrab::instance()
-        .workflows(repo.owner, repo.name)
-        .list_all_runs()
-        .branch(repo.branch.unwrap_or_default())
-        .per_page(repo.count.unwrap_or(1))
-        .actor(repo.actor.unwrap_or_default())
-        .send()
-        .await?;
-
-    Ok(workflows.items.iter().map(Into::into).collect())
+/// The items of all the pages of an endpoint that takes a page number, the
+/// pages are fetched until one isn't full.
+async fn get_all_pages<R, T>(route: &str, items: impl Fn(R) -> Vec<T>) -> octocrab::Result<Vec<T>>
+where
+    R: DeserializeOwned,
+{
+    let mut all = vec![];
+    let mut params = PageParams::default();
+
+    loop {
+        let page = items(octocrab::instance().get(route, Some(&params)).await?);
+        let is_full = page.len() >= usize::from(params.per_page);
+        all.extend(page);
+
+        if !is_full {
+            return Ok(all);
+        }
+        params.page += 1;
+    }
 }
@@ -0,0 +1,18 @@
+use std::env;
+
+/// Whether the app runs over SSH, where the browser and the native clipboard
+/// are the ones of the remote host rather than the user's.
+pub fn is_remote() -> bool {
+    env::var_os("SSH_CONNECTION").is_some() || env::var_os("SSH_TTY").is_some()
+}
+
+/// Whether there is a display to open a browser on.
+///
+/// Only X11 and Wayland need one to be set, macOS and Windows always have one.
+pub fn has_display() -> bool {
+    if cfg!(any(target_os = "macos", target_os = "windows")) {
+        return true;
+    }
+
+    env::var_os("DISPLAY").is_some() || env::var_os("WAYLAND_DISPLAY").is_some()
+}
@@ -0,0 +1,147 @@
+use chrono::TimeDelta;
+
+use crate::models::{WorkflowJob, WorkflowRun, WorkflowRunConclusion};
+
+/// Trend statistics of a workflow, computed over its last completed runs.
+#[derive(Debug, Clone, Default)]
+pub struct WorkflowStats {
+    pub runs: usize,
+    pub p50_duration: Option<TimeDelta>,
+    pub p95_duration: Option<TimeDelta>,
+    pub p50_queue_time: Option<TimeDelta>,
+    pub p95_queue_time: Option<TimeDelta>,
+    pub success_rate: Option<f64>,
+    /// The duration of each run in seconds, from the oldest to the newest.
+    pub durations: Vec<u64>,
+}
+
+impl WorkflowStats {
+    /// Compute the statistics of the given completed `runs`, and of the `jobs`
+    /// that were part of them.
+    pub fn compute(runs: &[WorkflowRun], jobs: &[WorkflowJob]) -> Self {
+        let now = chrono::Utc::now();
+
+        let mut runs: Vec<&WorkflowRun> = runs.iter().filter(|r| r.is_completed()).collect();
+        runs.sort_by_key(|r| r.start_time);
+
+        let durations: Vec<TimeDelta> = runs.iter().map(|r| r.duration(now)).collect();
+
+        let mut sorted_durations = durations.clone();
+        sorted_durations.sort();
+
+        let mut queue_times: Vec<TimeDelta> = jobs.iter().map(WorkflowJob::queue_time).collect();
+        queue_times.sort();
+
+        let successes = runs
+            .iter()
+            .filter(|r| matches!(r.conclusion, WorkflowRunConclusion::Success))
+            .count();
+        let success_rate = match runs.len() {
+            0 => None,
+            n => Some(successes as f64 / n as f64),
+        };
+
+        Self {
+            runs: runs.len(),
+            p50_duration: percentile(&sorted_durations, 50),
+            p95_duration: percentile(&sorted_durations, 95),
+            p50_queue_time: percentile(&queue_times, 50),
+            p95_queue_time: percentile(&queue_times, 95),
+            success_rate,
+            durations: durations
+                .iter()
+                .map(|d| d.num_seconds().max(0) as u64)
+                .collect(),
+        }
+    }
+}
+
+/// The nearest-rank percentile of an already sorted slice.
+fn percentile(sorted: &[TimeDelta], p: usize) -> Option<TimeDelta> {
+    if sorted.is_empty() {
+        return None;
+    }
+
+    let rank = (p * sorted.len()).div_ceil(100).max(1);
+
+    sorted.get(rank - 1).copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+    use fake::{Fake, Faker};
+
+    use super::*;
+    use crate::models::WorkflowRunStatus;
+
+    fn run(start: i64, seconds: i64, conclusion: WorkflowRunConclusion) -> WorkflowRun {
+        let start_time = Utc.timestamp_opt(start, 0).unwrap();
+
+        WorkflowRun {
+            start_time,
+            run_started_at: start_time,
+            updated_at: start_time + TimeDelta::seconds(seconds),
+            status: WorkflowRunStatus::Completed,
+            conclusion,
+            ..Faker.fake()
+        }
+    }
+
+    fn job(queued_seconds: i64) -> WorkflowJob {
+        let created_at = Utc.timestamp_opt(0, 0).unwrap();
+
+        WorkflowJob {
+            created_at,
+            started_at: created_at + TimeDelta::seconds(queued_seconds),
+            ..Faker.fake()
+        }
+    }
+
+    #[test]
+    fn compute_stats() {
+        let in_progress = WorkflowRun {
+            status: WorkflowRunStatus::InProgress,
+            ..run(0, 1000, WorkflowRunConclusion::Pending)
+        };
+        let runs = [
+            run(400, 40, WorkflowRunConclusion::Failure),
+            run(100, 10, WorkflowRunConclusion::Success),
+            in_progress,
+            run(300, 30, WorkflowRunConclusion::Success),
+            run(200, 20, WorkflowRunConclusion::Success),
+        ];
+        let jobs = [job(5), job(1), job(60)];
+
+        let stats = WorkflowStats::compute(&runs, &jobs);
+
+        assert_eq!(stats.runs, 4);
+        assert_eq!(stats.durations, vec![10, 20, 30, 40]);
+        assert_eq!(stats.p50_duration, Some(TimeDelta::seconds(20)));
+        assert_eq!(stats.p95_duration, Some(TimeDelta::seconds(40)));
+        assert_eq!(stats.p50_queue_time, Some(TimeDelta::seconds(5)));
+        assert_eq!(stats.p95_queue_time, Some(TimeDelta::seconds(60)));
+        assert_eq!(stats.success_rate, Some(0.75));
+    }
+
+    #[test]
+    fn compute_stats_without_runs() {
+        let stats = WorkflowStats::compute(&[], &[]);
+
+        assert_eq!(stats.runs, 0);
+        assert_eq!(stats.p50_duration, None);
+        assert_eq!(stats.success_rate, None);
+    }
+
+    #[test]
+    fn nearest_rank_percentile() {
+        let sorted: Vec<_> = (1..=10).map(TimeDelta::seconds).collect();
+
+        assert_eq!(percentile(&sorted, 0), Some(TimeDelta::seconds(1)));
+        assert_eq!(percentile(&sorted, 50), Some(TimeDelta::seconds(5)));
+        assert_eq!(percentile(&sorted, 95), Some(TimeDelta::seconds(10)));
+        assert_eq!(percentile(&sorted, 100), Some(TimeDelta::seconds(10)));
+        assert_eq!(percentile(&sorted[..1], 95), Some(TimeDelta::seconds(1)));
+        assert_eq!(percentile(&[], 50), None);
+    }
+}
@@ -0,0 +1,76 @@
+use std::any::Any;
+use std::future::Future;
+use std::time::Duration;
+
+use log::{error, info};
+use tokio::task::{AbortHandle, JoinHandle};
+use tokio::time::{self, Instant};
+
+use crate::backoff::Backoff;
+
+/// A task running this long is considered recovered.
+const STABLE_AFTER: Duration = Duration::from_secs(300);
+
+/// Run a background task, restarting it when it panics.
+///
+/// `on_crash` is called with the panic message and the delay before the
+/// restart, so that the crash can be shown rather than leaving stale data on
+/// screen. The delay grows while the task keeps crashing, and is reset once it
+/// has run for a while. Aborting the returned task aborts the supervised one.
+pub fn supervise<F, Fut>(
+    name: &'static str,
+    mut make_task: F,
+    on_crash: impl Fn(&str, Duration) + Send + 'static,
+) -> JoinHandle<()>
+where
+    F: FnMut() -> Fut + Send + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    tokio::spawn(async move {
+        let mut backoff = Backoff::new(Duration::from_secs(1), Duration::from_secs(60));
+
+        loop {
+            let started_at = Instant::now();
+            let task = tokio::spawn(make_task());
+            let guard = AbortOnDrop(task.abort_handle());
+
+            let err = match task.await {
+                Ok(()) => return,
+                Err(err) if err.is_cancelled() => return,
+                Err(err) => err,
+            };
+            drop(guard);
+
+            if started_at.elapsed() > STABLE_AFTER {
+                backoff.reset();
+            }
+            let delay = backoff.next_delay();
+
+            let message = panic_message(err.into_panic());
+            error!("The {} task crashed: {}", name, message);
+            on_crash(&message, delay);
+
+            time::sleep(delay).await;
+            info!("Restarting the {} task", name);
+        }
+    })
+}
+
+/// Aborts the supervised task with its supervisor.
+struct AbortOnDrop(AbortHandle);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => match payload.downcast::<&'static str>() {
+            Ok(message) => message.to_string(),
+            Err(_) => "unknown panic".to_string(),
+        },
+    }
+}
@@ -1,11 +1,13 @@
 use fake::faker::chrono::en::*;
 use fake::faker::company::en::*;
+use fake::faker::internet::en::*;
 use fake::faker::lorem::en::*;
+use fake::faker::name::en::*;
 use fake::rand::random;
 use fake::{Fake, Faker, Rng};
 use url::Url;
 
-use crate::models::{WorkflowJob, WorkflowRun};
+use crate::models::{PullRequest, WorkflowJob, WorkflowRun};
 
 #[cfg(feature = "mocks")]
 impl fake::Dummy<Faker> for WorkflowRun {
@@ -21,8 +23,12 @@ impl fake::Dummy<Faker> for WorkflowRun {
             owner, repo, run_id
         );
 
+        let start_time: chrono::DateTime<chrono::Utc> = DateTime().fake();
+        let run_started_at = start_time + chrono::TimeDelta::seconds((0..120).fake());
+
         Self {
             id: run_id.into(),
+            workflow_id: random::<u64>().into(),
             owner,
             repo,
             name: Sentence(2..4).fake(),
@@ -31,11 +37,27 @@ impl fake::Dummy<Faker> for WorkflowRun {
                 .replace(' ', "-")
                 .replace('.', "")
                 .to_lowercase(),
+            head_sha: fake_sha(),
             commit_message: format!("fake: {}", Bs().fake::<String>()),
-            start_time: DateTime().fake(),
+            start_time,
+            run_started_at,
+            updated_at: run_started_at + chrono::TimeDelta::seconds((0..3600).fake()),
             status: Faker.fake(),
             conclusion: Faker.fake(),
             html_url: Url::parse(&url).unwrap(),
+            commit_author: Name().fake(),
+            event: ["push", "pull_request", "schedule", "workflow_dispatch"]
+                [(0..4).fake::<usize>()]
+            .to_string(),
+            actor: Username().fake(),
+            triggering_actor: Username().fake(),
+            run_number: (1..5000).fake(),
+            run_attempt: (1..3).fake(),
+            pull_request: random::<bool>().then(|| PullRequest {
+                number: (1..2000).fake(),
+                title: Some(Sentence(3..6).fake()),
+            }),
+            check_suite_id: Some(random::<u32>().into()),
         }
     }
 }
@@ -54,14 +76,30 @@ impl fake::Dummy<Faker> for WorkflowJob {
             owner, repo, run_id, job_id
         );
 
+        let created_at: chrono::DateTime<chrono::Utc> = DateTime().fake();
+
         Self {
             id: job_id.into(),
+            run_id: run_id.into(),
             name: Sentence(2..4).fake(),
-            started_at: DateTime().fake(),
+            created_at,
+            started_at: created_at + chrono::TimeDelta::seconds((0..300).fake()),
             completed_at: DateTime().fake(),
             status: Faker.fake(),
             conclusion: Faker.fake(),
             html_url: Url::parse(&url).unwrap(),
+            run_attempt: 1,
+            head_sha: fake_sha(),
+            labels: match random::<bool>() {
+                true => vec!["self-hosted".to_string(), "linux".to_string()],
+                false => vec!["ubuntu-latest".to_string()],
+            },
         }
     }
 }
+
+fn fake_sha() -> String {
+    (0..40)
+        .map(|_| char::from_digit((0..16).fake(), 16).unwrap())
+        .collect()
+}
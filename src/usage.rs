@@ -0,0 +1,106 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{DateTime, Datelike, NaiveDate, TimeDelta, Utc};
+
+use crate::models::{RunnerOs, WorkflowJob, WorkflowJobStatus};
+
+const MS_PER_MINUTE: u64 = 60_000;
+
+/// The Actions minutes of a workflow in the current billing period.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkflowUsage {
+    /// `owner/repo`.
+    pub repo: String,
+    pub workflow: String,
+    pub billable_ms: BTreeMap<RunnerOs, u64>,
+    /// Whether the time was estimated from the jobs rather than reported by
+    /// GitHub.
+    pub estimated: bool,
+}
+
+impl WorkflowUsage {
+    pub fn minutes(&self, os: RunnerOs) -> u64 {
+        self.billable_ms
+            .get(&os)
+            .map_or(0, |ms| ms.div_ceil(MS_PER_MINUTE))
+    }
+
+    pub fn total_minutes(&self) -> u64 {
+        RunnerOs::ALL.iter().map(|os| self.minutes(*os)).sum()
+    }
+
+    /// The included minutes used up: a minute on Windows or macOS counts for
+    /// more than a minute on Linux.
+    pub fn weighted_minutes(&self) -> u64 {
+        RunnerOs::ALL
+            .iter()
+            .map(|os| self.minutes(*os) * os.multiplier())
+            .sum()
+    }
+}
+
+/// The start of the billing period `now` is in, the first day of the month.
+pub fn billing_period_start(now: DateTime<Utc>) -> DateTime<Utc> {
+    NaiveDate::from_ymd_opt(now.year(), now.month(), 1)
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .map_or(now, |d| d.and_utc())
+}
+
+/// Estimate the billable time of the jobs started since `since`, per OS.
+///
+/// GitHub rounds each job up to the minute. The jobs on self-hosted runners
+/// aren't billed, and neither are the ones whose OS is unknown, e.g. the jobs
+/// recorded before their labels were.
+pub fn estimate(jobs: &[WorkflowJob], since: DateTime<Utc>) -> BTreeMap<RunnerOs, u64> {
+    let mut billable_ms = BTreeMap::new();
+
+    for job in jobs.iter().filter(|j| j.started_at >= since) {
+        let (Some(os), Some(completed_at)) = (job.runner_os(), job.completed_at) else {
+            continue;
+        };
+        if !matches!(job.status, WorkflowJobStatus::Completed) {
+            continue;
+        }
+
+        let ms = (completed_at - job.started_at)
+            .max(TimeDelta::zero())
+            .num_milliseconds() as u64;
+        *billable_ms.entry(os).or_default() += ms.div_ceil(MS_PER_MINUTE) * MS_PER_MINUTE;
+    }
+
+    billable_ms
+}
+
+/// Sort the workflows by the minutes they use up, the most expensive first.
+pub fn rank(usages: &mut [WorkflowUsage]) {
+    usages.sort_by(|a, b| {
+        b.weighted_minutes()
+            .cmp(&a.weighted_minutes())
+            .then(b.total_minutes().cmp(&a.total_minutes()))
+            .then_with(|| (&a.repo, &a.workflow).cmp(&(&b.repo, &b.workflow)))
+    });
+}
+
+/// The minutes of all the workflows, per OS.
+pub fn minutes_per_os(usages: &[WorkflowUsage]) -> BTreeMap<RunnerOs, u64> {
+    RunnerOs::ALL
+        .iter()
+        .map(|os| (*os, usages.iter().map(|u| u.minutes(*os)).sum()))
+        .collect()
+}
+
+/// The weighted minutes of each repository, the most expensive first.
+pub fn weighted_minutes_per_repo(usages: &[WorkflowUsage]) -> Vec<(String, u64)> {
+    let mut per_repo: HashMap<&str, u64> = HashMap::new();
+    for usage in usages {
+        *per_repo.entry(&usage.repo).or_default() += usage.weighted_minutes();
+    }
+
+    let mut per_repo: Vec<_> = per_repo
+        .into_iter()
+        .map(|(repo, minutes)| (repo.to_string(), minutes))
+        .collect();
+    per_repo.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+    per_repo
+}
@@ -1,3 +1,10 @@
+mod format;
+mod layout;
+mod mouse;
+mod refresh;
+mod runners;
 mod state;
+pub mod theme;
+mod usage;
 mod workflow_details;
 pub mod workflow_run;
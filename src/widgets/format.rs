@@ -0,0 +1,128 @@
+use chrono::{DateTime, TimeDelta, Utc};
+
+use crate::configuration::{TimeSettings, TimeZoneSetting};
+use crate::widgets::state::LoadingState;
+
+/// Format a duration as e.g. `1h 02m 03s`, `12m 04s` or `45s`.
+pub(crate) fn format_duration(d: TimeDelta) -> String {
+    let secs = d.num_seconds().max(0);
+    let (hours, minutes, seconds) = (secs / 3600, (secs % 3600) / 60, secs % 60);
+
+    if hours > 0 {
+        format!("{}h {:02}m {:02}s", hours, minutes, seconds)
+    } else if minutes > 0 {
+        format!("{}m {:02}s", minutes, seconds)
+    } else {
+        format!("{}s", seconds)
+    }
+}
+
+/// Format a size in bytes, e.g. `512 B`, `1.5 KB` or `12.3 MB`.
+pub(crate) fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+/// Format the age of a timestamp, e.g. `3m ago`, or `in 3m` for a timestamp in
+/// the future.
+pub(crate) fn format_age(d: TimeDelta) -> String {
+    let secs = d.num_seconds();
+    let abs = secs.abs();
+
+    let value = if abs < 60 {
+        format!("{}s", abs)
+    } else if abs < 3600 {
+        format!("{}m", abs / 60)
+    } else if abs < 86400 {
+        format!("{}h", abs / 3600)
+    } else {
+        format!("{}d", abs / 86400)
+    };
+
+    if secs < 0 {
+        format!("in {}", value)
+    } else {
+        format!("{} ago", value)
+    }
+}
+
+/// Formats the timestamps displayed by the widgets, according to the time
+/// settings.
+///
+/// The relative times are computed when formatting, so they stay up to date as
+/// the widgets are redrawn on every frame.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct TimeFormatter {
+    settings: TimeSettings,
+}
+
+impl TimeFormatter {
+    pub fn new(settings: &TimeSettings) -> Self {
+        Self {
+            settings: settings.clone(),
+        }
+    }
+
+    pub fn time(&self, t: DateTime<Utc>) -> String {
+        if self.settings.relative {
+            return format_age(Utc::now() - t);
+        }
+
+        self.absolute(t)
+    }
+
+    pub fn absolute(&self, t: DateTime<Utc>) -> String {
+        let format = self.settings.format.as_str();
+
+        match self.settings.timezone {
+            TimeZoneSetting::Local => t.with_timezone(&chrono::Local).format(format).to_string(),
+            TimeZoneSetting::Utc => t.format(format).to_string(),
+            TimeZoneSetting::Fixed(offset) => t.with_timezone(&offset).format(format).to_string(),
+        }
+    }
+
+    /// Format the duration of something that may still be running.
+    pub fn duration(&self, d: TimeDelta, running: bool) -> String {
+        if running && self.settings.relative {
+            return format!("running {}", format_duration(d));
+        }
+
+        format_duration(d)
+    }
+
+    pub fn loading_state(&self, state: &LoadingState) -> String {
+        let now = Utc::now();
+
+        match state {
+            LoadingState::Loaded(time) if self.settings.relative => {
+                format!("Refreshed {}", format_age(now - time.to_utc()))
+            }
+            LoadingState::Loaded(time) => {
+                format!("Last refreshed at {}", self.absolute(time.to_utc()))
+            }
+            LoadingState::Stale {
+                refreshed_at,
+                retry_at,
+            } => format!(
+                "Offline, data is {} old, retrying in {}",
+                format_duration(now - refreshed_at.to_utc()),
+                format_duration(retry_at.to_utc() - now)
+            ),
+            LoadingState::Idle => String::new(),
+            LoadingState::Loading => "Loading...".to_string(),
+            LoadingState::Error(err) => err.clone(),
+        }
+    }
+}
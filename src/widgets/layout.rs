@@ -0,0 +1,487 @@
+use std::collections::HashSet;
+
+use ratatui::buffer::Buffer;
+use ratatui::layout::{Constraint, Flex, Layout, Rect};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span, Text};
+use ratatui::widgets::{Block, BorderType, Cell, Paragraph, Row, Widget, Wrap};
+use tui_big_text::{BigText, PixelSize};
+
+use crate::configuration::LayoutMode;
+use crate::models::WorkflowRun;
+use crate::widgets::format::TimeFormatter;
+use crate::widgets::theme::Theme;
+
+/// Tables narrower than this shorten the project and the conclusion.
+const COMPACT_WIDTH: u16 = 100;
+/// Panes narrower than this, and taller than wide, show cards in auto mode.
+const CARDS_MAX_WIDTH: u16 = 80;
+/// A card takes 3 lines, and a blank line separates the cards.
+pub(crate) const CARD_HEIGHT: u16 = 4;
+/// The sizes of the big text of the wallboard tiles, from the largest, with
+/// the width and height of a glyph in cells.
+const BIG_TEXT_SIZES: [(PixelSize, u16, u16); 3] = [
+    (PixelSize::Full, 8, 8),
+    (PixelSize::HalfHeight, 8, 4),
+    (PixelSize::Quadrant, 4, 4),
+];
+
+/// The layout to use for an area, resolving the auto mode.
+pub(crate) fn resolve(mode: LayoutMode, area: Rect) -> LayoutMode {
+    match mode {
+        // A cell is about twice as high as it is wide.
+        LayoutMode::Auto if area.width < CARDS_MAX_WIDTH && area.height * 2 > area.width => {
+            LayoutMode::Cards
+        }
+        LayoutMode::Auto => LayoutMode::Table,
+        mode => mode,
+    }
+}
+
+/// The columns of the run table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Column {
+    Project,
+    Branch,
+    Workflow,
+    Commit,
+    StartTime,
+    Duration,
+    Status,
+    Conclusion,
+}
+
+impl Column {
+    const ALL: [Column; 8] = [
+        Column::Project,
+        Column::Branch,
+        Column::Workflow,
+        Column::Commit,
+        Column::StartTime,
+        Column::Duration,
+        Column::Status,
+        Column::Conclusion,
+    ];
+
+    /// The column is dropped from the tables narrower than this.
+    fn min_table_width(&self) -> u16 {
+        match self {
+            Column::StartTime => 140,
+            Column::Branch => 120,
+            Column::Status => 100,
+            Column::Duration => 80,
+            Column::Commit => 60,
+            Column::Project | Column::Workflow | Column::Conclusion => 0,
+        }
+    }
+
+    fn title(&self) -> &'static str {
+        match self {
+            Column::Project => "Project",
+            Column::Branch => "Branch",
+            Column::Workflow => "Workflow Name",
+            Column::Commit => "Commit Title",
+            Column::StartTime => "Start Time",
+            Column::Duration => "Duration",
+            Column::Status => "Status",
+            Column::Conclusion => "Completion",
+        }
+    }
+
+    fn constraint(&self, compact: bool) -> Constraint {
+        match (self, compact) {
+            (Column::Project, false) => Constraint::Max(50),
+            (Column::Project, true) => Constraint::Max(24),
+            (Column::Branch, _) => Constraint::Max(32),
+            (Column::Workflow, _) => Constraint::Max(32),
+            (Column::Commit, _) => Constraint::Max(128),
+            (Column::StartTime, _) => Constraint::Max(32),
+            (Column::Duration, _) => Constraint::Length(12),
+            (Column::Status, _) => Constraint::Length(16),
+            (Column::Conclusion, false) => Constraint::Length(16),
+            (Column::Conclusion, true) => Constraint::Length(4),
+        }
+    }
+}
+
+/// The columns that fit in a table of `width` cells, and whether they are
+/// shortened.
+#[derive(Debug, Clone)]
+pub(crate) struct Columns {
+    columns: Vec<Column>,
+    compact: bool,
+}
+
+impl Columns {
+    pub fn new(width: u16) -> Self {
+        Self {
+            columns: Column::ALL
+                .into_iter()
+                .filter(|c| width >= c.min_table_width())
+                .collect(),
+            compact: width < COMPACT_WIDTH,
+        }
+    }
+
+    pub fn widths(&self) -> Vec<Constraint> {
+        self.columns
+            .iter()
+            .map(|c| c.constraint(self.compact))
+            .collect()
+    }
+
+    pub fn header<'a>(&self) -> Row<'a> {
+        Row::new(self.columns.iter().map(|c| c.title()))
+    }
+
+    pub fn row<'a>(
+        &self,
+        r: &WorkflowRun,
+        loading: bool,
+        time_formatter: &TimeFormatter,
+        theme: &Theme,
+    ) -> Row<'a> {
+        let cells = self.columns.iter().map(|column| match column {
+            Column::Project => {
+                let project = match self.compact {
+                    true => r.repo.clone(),
+                    false => format!("{}/{}", r.owner, r.repo),
+                };
+                // Mark the runs of the repositories being refreshed
+                match loading {
+                    true => Cell::from(format!("{} {}", theme.symbols.loading, project)),
+                    false => Cell::from(project),
+                }
+            }
+            Column::Branch => Cell::from(r.branch.clone()),
+            Column::Workflow => Cell::from(r.name.clone()),
+            Column::Commit => Cell::from(commit_title(r).to_string()),
+            Column::StartTime => Cell::from(time_formatter.time(r.start_time)),
+            Column::Duration => Cell::from(duration(r, time_formatter)),
+            Column::Status => theme.run_status(&r.status),
+            Column::Conclusion => theme.run_conclusion(&r.conclusion, self.compact),
+        });
+
+        Row::new(cells)
+    }
+}
+
+/// A run as a card of 3 lines: the outcome and the workflow, the commit, and
+/// the timings.
+pub(crate) fn card_row<'a>(
+    r: &WorkflowRun,
+    loading: bool,
+    time_formatter: &TimeFormatter,
+    theme: &Theme,
+) -> Row<'a> {
+    let mut title = vec![
+        theme.run_symbol(r),
+        Span::raw(" "),
+        Span::styled(
+            format!("{}/{}", r.owner, r.repo),
+            Style::new().add_modifier(Modifier::BOLD),
+        ),
+        Span::raw(format!(" · {}", r.name)),
+    ];
+    if loading {
+        title.push(Span::raw(format!(" {}", theme.symbols.loading)));
+    }
+
+    let text = Text::from(vec![
+        Line::from(title),
+        Line::from(format!("{} · {}", r.branch, commit_title(r))),
+        Line::from(format!(
+            "{} · {} · {}",
+            time_formatter.time(r.start_time),
+            duration(r, time_formatter),
+            r.status
+        )),
+    ]);
+
+    Row::new([Cell::from(text)]).height(CARD_HEIGHT)
+}
+
+/// Large tiles of the latest run of each workflow and branch, when it is
+/// failing or in progress, to be read from across the room.
+pub(crate) fn render_wallboard(
+    runs: &[WorkflowRun],
+    time_formatter: &TimeFormatter,
+    theme: &Theme,
+    area: Rect,
+    buf: &mut Buffer,
+) {
+    // The runs are sorted from the latest.
+    let mut seen = HashSet::new();
+    let runs: Vec<_> = runs
+        .iter()
+        .filter(|r| seen.insert((&r.owner, &r.repo, r.workflow_id, &r.branch)))
+        .filter(|r| !r.is_completed() || r.conclusion.is_failure())
+        .collect();
+
+    if runs.is_empty() {
+        let block = Block::bordered()
+            .border_type(BorderType::Thick)
+            .border_style(theme.success_style());
+        let [line] = block
+            .inner(area)
+            .layout(&Layout::vertical([Constraint::Length(1)]).flex(Flex::Center));
+
+        block.render(area, buf);
+        Line::from(format!("{} All green", theme.symbols.success))
+            .centered()
+            .render(line, buf);
+        return;
+    }
+
+    // A square grid, the last row may not be full.
+    let cols = (runs.len() as f64).sqrt().ceil() as usize;
+    let rows = runs.len().div_ceil(cols);
+
+    let row_areas = area.layout_vec(&Layout::vertical(vec![Constraint::Fill(1); rows]));
+
+    for (row_area, row_runs) in row_areas.iter().zip(runs.chunks(cols)) {
+        let tile_areas = row_area.layout_vec(&Layout::horizontal(vec![Constraint::Fill(1); cols]));
+
+        for (tile_area, run) in tile_areas.iter().zip(row_runs) {
+            render_tile(run, time_formatter, theme, *tile_area, buf);
+        }
+    }
+}
+
+fn render_tile(
+    r: &WorkflowRun,
+    time_formatter: &TimeFormatter,
+    theme: &Theme,
+    area: Rect,
+    buf: &mut Buffer,
+) {
+    let style = theme.run_style(r);
+    let block = Block::bordered()
+        .border_type(BorderType::Thick)
+        .border_style(style);
+
+    let inner = block.inner(area);
+    block.render(area, buf);
+
+    // The repository and the status in big text, when it fits with the details
+    let status = match r.is_completed() {
+        true => r.conclusion.to_string(),
+        false => r.status.to_string(),
+    }
+    .replace('_', " ");
+    let name = Line::from(r.name.clone()).style(Style::new().add_modifier(Modifier::BOLD));
+    let branch = Line::from(r.branch.clone());
+    let elapsed = Line::from(duration(r, time_formatter));
+    // The name, the branch and the duration, under a blank line
+    let details_height = 4;
+
+    let big_text = inner
+        .height
+        .checked_sub(details_height)
+        .and_then(|height| big_text_size(&[&r.repo, &status], inner.width, height));
+    if let Some((pixel_size, big_height)) = big_text {
+        let [big_area, _, details_area] = inner.layout(
+            &Layout::vertical([
+                Constraint::Length(big_height),
+                Constraint::Length(1),
+                Constraint::Length(details_height - 1),
+            ])
+            .flex(Flex::Center),
+        );
+
+        BigText::builder()
+            .pixel_size(pixel_size)
+            .style(style)
+            .lines(vec![Line::from(r.repo.clone()), Line::from(status)])
+            .centered()
+            .build()
+            .render(big_area, buf);
+        Paragraph::new(vec![name, branch, elapsed])
+            .centered()
+            .render(details_area, buf);
+        return;
+    }
+
+    let lines = vec![
+        Line::from(vec![
+            theme.run_symbol(r),
+            Span::raw(" "),
+            Span::styled(
+                format!("{}/{}", r.owner, r.repo),
+                style.add_modifier(Modifier::BOLD),
+            ),
+        ]),
+        Line::from(""),
+        name,
+        branch,
+        Line::from(""),
+        elapsed,
+    ];
+
+    let height = (lines.len() as u16).min(inner.height);
+    let [text_area] =
+        inner.layout(&Layout::vertical([Constraint::Length(height)]).flex(Flex::Center));
+
+    Paragraph::new(lines)
+        .centered()
+        .wrap(Wrap { trim: true })
+        .render(text_area, buf);
+}
+
+/// The largest size to write each of `lines` in big text on a line of its own
+/// within `width` by `height` cells, with the height it takes.
+fn big_text_size(lines: &[&str], width: u16, height: u16) -> Option<(PixelSize, u16)> {
+    let longest = lines.iter().map(|l| l.chars().count()).max()?;
+
+    BIG_TEXT_SIZES
+        .into_iter()
+        .find(|(_, glyph_width, glyph_height)| {
+            longest * *glyph_width as usize <= width as usize
+                && lines.len() * *glyph_height as usize <= height as usize
+        })
+        .map(|(size, _, glyph_height)| (size, lines.len() as u16 * glyph_height))
+}
+
+fn commit_title(r: &WorkflowRun) -> &str {
+    r.commit_message.split('\n').next().unwrap_or_default()
+}
+
+fn duration(r: &WorkflowRun, time_formatter: &TimeFormatter) -> String {
+    time_formatter.duration(r.duration(chrono::Utc::now()), !r.is_completed())
+}
+
+#[cfg(test)]
+mod tests {
+    use fake::{Fake, Faker};
+
+    use super::*;
+    use crate::configuration::TimeSettings;
+    use crate::models::{WorkflowRunConclusion, WorkflowRunStatus};
+
+    #[test]
+    fn columns_are_dropped_as_the_table_narrows() {
+        let columns = |width| Columns::new(width).columns;
+
+        assert_eq!(columns(140), Column::ALL);
+        assert_eq!(
+            columns(139),
+            [
+                Column::Project,
+                Column::Branch,
+                Column::Workflow,
+                Column::Commit,
+                Column::Duration,
+                Column::Status,
+                Column::Conclusion,
+            ]
+        );
+        assert_eq!(
+            columns(119),
+            [
+                Column::Project,
+                Column::Workflow,
+                Column::Commit,
+                Column::Duration,
+                Column::Status,
+                Column::Conclusion,
+            ]
+        );
+        assert_eq!(
+            columns(99),
+            [
+                Column::Project,
+                Column::Workflow,
+                Column::Commit,
+                Column::Duration,
+                Column::Conclusion,
+            ]
+        );
+        assert_eq!(
+            columns(79),
+            [
+                Column::Project,
+                Column::Workflow,
+                Column::Commit,
+                Column::Conclusion,
+            ]
+        );
+        assert_eq!(
+            columns(59),
+            [Column::Project, Column::Workflow, Column::Conclusion]
+        );
+        assert_eq!(
+            columns(0),
+            [Column::Project, Column::Workflow, Column::Conclusion]
+        );
+    }
+
+    #[test]
+    fn narrow_tables_are_compact() {
+        assert!(!Columns::new(COMPACT_WIDTH).compact);
+        assert!(Columns::new(COMPACT_WIDTH - 1).compact);
+        assert_eq!(
+            Columns::new(COMPACT_WIDTH - 1).widths()[0],
+            Constraint::Max(24)
+        );
+    }
+
+    #[test]
+    fn auto_mode_shows_cards_in_narrow_and_tall_panes() {
+        let resolve = |width, height| resolve(LayoutMode::Auto, Rect::new(0, 0, width, height));
+
+        assert_eq!(resolve(79, 40), LayoutMode::Cards);
+        assert_eq!(resolve(80, 40), LayoutMode::Table);
+        assert_eq!(resolve(79, 39), LayoutMode::Table);
+        assert_eq!(
+            super::resolve(LayoutMode::Table, Rect::new(0, 0, 20, 40)),
+            LayoutMode::Table
+        );
+    }
+
+    #[test]
+    fn big_text_is_the_largest_size_that_fits() {
+        // 6 characters on 2 lines
+        let lines = ["gh-tui", "failure"];
+
+        assert_eq!(big_text_size(&lines, 56, 16), Some((PixelSize::Full, 16)));
+        assert_eq!(
+            big_text_size(&lines, 56, 15),
+            Some((PixelSize::HalfHeight, 8))
+        );
+        assert_eq!(
+            big_text_size(&lines, 55, 16),
+            Some((PixelSize::Quadrant, 8))
+        );
+        assert_eq!(big_text_size(&lines, 27, 16), None);
+        assert_eq!(big_text_size(&lines, 56, 7), None);
+    }
+
+    #[test]
+    fn wallboard_tiles_use_big_text_when_they_fit() {
+        let run = WorkflowRun {
+            repo: "api".to_string(),
+            status: WorkflowRunStatus::Completed,
+            conclusion: WorkflowRunConclusion::Failure,
+            ..Faker.fake()
+        };
+        let time_formatter = TimeFormatter::new(&TimeSettings::default());
+        let theme = Theme::default();
+
+        let render = |width, height| {
+            let area = Rect::new(0, 0, width, height);
+            let mut buf = Buffer::empty(area);
+            render_wallboard(
+                std::slice::from_ref(&run),
+                &time_formatter,
+                &theme,
+                area,
+                &mut buf,
+            );
+            buf
+        };
+        let has_block = |buf: &Buffer| buf.content().iter().any(|c| c.symbol() == "█");
+
+        assert!(has_block(&render(80, 30)));
+        assert!(!has_block(&render(30, 10)));
+    }
+}
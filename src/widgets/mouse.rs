@@ -0,0 +1,116 @@
+use std::time::{Duration, Instant};
+
+use ratatui::layout::{Position, Rect};
+
+/// The index of the row of a bordered table at `position`, given the area,
+/// the height of the header (0 without one) and the scroll offset of the table
+/// as of the last render.
+///
+/// The row may be past the end of the table.
+pub(crate) fn row_at(
+    area: Rect,
+    header_height: u16,
+    offset: usize,
+    row_height: u16,
+    position: Position,
+) -> Option<usize> {
+    // The borders surround the header and the rows.
+    let body = Rect {
+        x: area.x + 1,
+        y: area.y + 1 + header_height,
+        width: area.width.saturating_sub(2),
+        height: area.height.saturating_sub(header_height + 2),
+    };
+
+    if !body.contains(position) {
+        return None;
+    }
+
+    Some(offset + usize::from((position.y - body.y) / row_height.max(1)))
+}
+
+/// Detects the double-clicks on the rows of a table.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct DoubleClick {
+    last_click: Option<(Instant, usize)>,
+}
+
+impl DoubleClick {
+    const DELAY: Duration = Duration::from_millis(500);
+
+    /// Record a click on a row, returns whether it is the second click of a
+    /// double-click.
+    pub fn click(&mut self, row: usize) -> bool {
+        let now = Instant::now();
+
+        let double = self
+            .last_click
+            .is_some_and(|(t, r)| r == row && now - t < Self::DELAY);
+
+        // A third click starts a new double-click.
+        self.last_click = if double { None } else { Some((now, row)) };
+
+        double
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AREA: Rect = Rect::new(10, 5, 40, 12);
+
+    #[test]
+    fn row_under_the_header() {
+        // The top border is at y = 5, the header at y = 6.
+        assert_eq!(row_at(AREA, 1, 0, 1, Position::new(20, 5)), None);
+        assert_eq!(row_at(AREA, 1, 0, 1, Position::new(20, 6)), None);
+        assert_eq!(row_at(AREA, 1, 0, 1, Position::new(20, 7)), Some(0));
+        assert_eq!(row_at(AREA, 1, 0, 1, Position::new(20, 9)), Some(2));
+    }
+
+    #[test]
+    fn row_without_a_header() {
+        assert_eq!(row_at(AREA, 0, 0, 1, Position::new(20, 6)), Some(0));
+        assert_eq!(row_at(AREA, 0, 0, 2, Position::new(20, 7)), Some(0));
+        assert_eq!(row_at(AREA, 0, 0, 2, Position::new(20, 8)), Some(1));
+    }
+
+    #[test]
+    fn row_on_the_borders() {
+        // The bottom border is at y = 16, the side ones at x = 10 and 49.
+        assert_eq!(row_at(AREA, 1, 0, 1, Position::new(20, 15)), Some(8));
+        assert_eq!(row_at(AREA, 1, 0, 1, Position::new(20, 16)), None);
+        assert_eq!(row_at(AREA, 1, 0, 1, Position::new(10, 8)), None);
+        assert_eq!(row_at(AREA, 1, 0, 1, Position::new(11, 8)), Some(1));
+        assert_eq!(row_at(AREA, 1, 0, 1, Position::new(48, 8)), Some(1));
+        assert_eq!(row_at(AREA, 1, 0, 1, Position::new(49, 8)), None);
+        assert_eq!(row_at(AREA, 1, 0, 1, Position::new(60, 8)), None);
+    }
+
+    #[test]
+    fn row_of_a_scrolled_table() {
+        assert_eq!(row_at(AREA, 1, 20, 1, Position::new(20, 7)), Some(20));
+        assert_eq!(row_at(AREA, 0, 3, 2, Position::new(20, 9)), Some(4));
+    }
+
+    #[test]
+    fn row_of_a_tiny_area() {
+        let area = Rect::new(0, 0, 10, 2);
+
+        assert_eq!(row_at(area, 1, 0, 1, Position::new(5, 1)), None);
+        assert_eq!(row_at(area, 0, 0, 0, Position::new(5, 1)), None);
+    }
+
+    #[test]
+    fn double_click() {
+        let mut clicks = DoubleClick::default();
+
+        assert!(!clicks.click(1));
+        assert!(clicks.click(1));
+        // A third click starts a new double-click.
+        assert!(!clicks.click(1));
+        assert!(!clicks.click(2));
+        assert!(clicks.click(2));
+    }
+}
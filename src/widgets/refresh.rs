@@ -0,0 +1,101 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use crate::configuration::RefreshSettings;
+
+/// Decides how often the data is refreshed.
+///
+/// The focus of the terminal is shared between the clones of a policy, so that
+/// the focus events received by one widget apply to all of them.
+#[derive(Debug, Clone)]
+pub struct RefreshPolicy {
+    settings: RefreshSettings,
+    focused: Arc<AtomicBool>,
+}
+
+impl Default for RefreshPolicy {
+    fn default() -> Self {
+        Self::new(&RefreshSettings::default())
+    }
+}
+
+impl RefreshPolicy {
+    pub fn new(settings: &RefreshSettings) -> Self {
+        Self {
+            settings: settings.clone(),
+            focused: Arc::new(AtomicBool::new(true)),
+        }
+    }
+
+    pub fn set_focused(&self, focused: bool) {
+        self.focused.store(focused, Ordering::Relaxed);
+    }
+
+    /// The refresh interval of data with the given `interval` override, in
+    /// seconds, and whether some of it is still running.
+    ///
+    /// When the polling is adaptive, an override scales the active and idle
+    /// intervals as it scales the default interval, e.g. halving the interval
+    /// of a repository halves them too.
+    pub fn interval(&self, interval: Option<u64>, running: bool) -> Duration {
+        let default = self.settings.interval;
+        let base = interval.unwrap_or(default);
+
+        let secs = if !self.settings.adaptive {
+            base
+        } else if !self.focused.load(Ordering::Relaxed) || !running {
+            default.max(self.settings.idle_interval) * base / default.max(1)
+        } else {
+            default.min(self.settings.active_interval) * base / default.max(1)
+        };
+
+        Duration::from_secs(secs.max(1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(adaptive: bool) -> RefreshPolicy {
+        RefreshPolicy::new(&RefreshSettings {
+            interval: 60,
+            adaptive,
+            active_interval: 10,
+            idle_interval: 300,
+        })
+    }
+
+    #[test]
+    fn fixed_interval() {
+        let policy = policy(false);
+
+        assert_eq!(policy.interval(None, true), Duration::from_secs(60));
+        assert_eq!(policy.interval(None, false), Duration::from_secs(60));
+        assert_eq!(policy.interval(Some(30), false), Duration::from_secs(30));
+        assert_eq!(policy.interval(Some(0), false), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn adaptive_interval() {
+        let policy = policy(true);
+
+        assert_eq!(policy.interval(None, true), Duration::from_secs(10));
+        assert_eq!(policy.interval(None, false), Duration::from_secs(300));
+
+        policy.set_focused(false);
+        assert_eq!(policy.interval(None, true), Duration::from_secs(300));
+    }
+
+    #[test]
+    fn adaptive_interval_of_a_repository() {
+        let policy = policy(true);
+
+        assert_eq!(policy.interval(Some(30), true), Duration::from_secs(5));
+        assert_eq!(policy.interval(Some(30), false), Duration::from_secs(150));
+        assert_eq!(policy.interval(Some(600), true), Duration::from_secs(100));
+        assert_eq!(policy.interval(Some(600), false), Duration::from_secs(3000));
+        assert_eq!(policy.interval(Some(1), true), Duration::from_secs(1));
+    }
+}
@@ -0,0 +1,410 @@
+use std::sync::{Arc, RwLock};
+
+use chrono::Utc;
+use log::error;
+use ratatui::buffer::Buffer;
+use ratatui::layout::{Constraint, Rect};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Cell, Paragraph, Row, StatefulWidget, Table, TableState, Widget};
+use tokio::task::{AbortHandle, JoinSet};
+use tokio::time;
+
+use crate::models::{Runner, RunnerScope, WorkflowJob, WorkflowJobStatus, WorkflowRun};
+use crate::service::workflows::{GitHubService, Service};
+use crate::supervisor::supervise;
+use crate::widgets::format::format_duration;
+use crate::widgets::refresh::RefreshPolicy;
+use crate::widgets::state::{LoadingState, RwLockExt};
+use crate::widgets::theme::Theme;
+
+/// What the popup shows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunnersView {
+    /// The self-hosted runners, with their status and their labels.
+    Runners,
+    /// The jobs waiting for a runner, with the runners that could pick them.
+    Queue,
+}
+
+/// The runs still going, the queued jobs are looked for in their jobs.
+pub type ActiveRuns = Arc<dyn Fn() -> Vec<WorkflowRun> + Send + Sync>;
+
+#[derive(Debug, Default)]
+struct RunnersState {
+    view: Option<RunnersView>,
+    runners: Vec<Runner>,
+    /// The scopes whose runners couldn't be listed, e.g. without the admin
+    /// permission.
+    failed_scopes: Vec<RunnerScope>,
+    queued_jobs: Vec<QueuedJob>,
+    loading_state: LoadingState,
+    table_state: TableState,
+    page_size: usize,
+    /// The background task refreshing the runners and the queue.
+    task: Option<AbortHandle>,
+}
+
+#[derive(Debug, Clone)]
+struct QueuedJob {
+    repo: String,
+    workflow: String,
+    job: WorkflowJob,
+}
+
+/// The self-hosted runners and the queued jobs, shown over the runs.
+///
+/// They are refreshed in the background while they are shown.
+#[derive(Debug, Clone)]
+pub(crate) struct RunnersWidget {
+    github_service: Arc<dyn GitHubService>,
+    state: Arc<RwLock<RunnersState>>,
+    refresh_policy: RefreshPolicy,
+    theme: Theme,
+    /// The hint at the bottom of the popup.
+    close_hint: String,
+}
+
+impl Default for RunnersWidget {
+    fn default() -> Self {
+        Self {
+            github_service: Arc::new(Service {}),
+            state: Arc::new(RwLock::new(RunnersState::default())),
+            refresh_policy: RefreshPolicy::default(),
+            theme: Theme::default(),
+            close_hint: String::new(),
+        }
+    }
+}
+
+impl RunnersWidget {
+    pub fn new(
+        github_service: Arc<dyn GitHubService>,
+        refresh_policy: RefreshPolicy,
+        theme: Theme,
+        close_hint: String,
+    ) -> Self {
+        Self {
+            github_service,
+            refresh_policy,
+            theme,
+            close_hint,
+            ..Default::default()
+        }
+    }
+
+    pub fn view(&self) -> Option<RunnersView> {
+        self.state.read_or_recover().view
+    }
+
+    /// Show the runners or the queue, and start refreshing them.
+    pub fn show(&self, view: RunnersView, scopes: Vec<RunnerScope>, active_runs: ActiveRuns) {
+        let mut state = self.state.write_or_recover();
+        state.view = Some(view);
+        state.table_state = TableState::default().with_selected(0);
+        if state.task.is_some() {
+            return;
+        }
+
+        state.loading_state = LoadingState::Loading;
+        let this = self.clone();
+        let on_crash = self.state.clone();
+        let task = supervise(
+            "runners",
+            move || this.clone().sync_data(scopes.clone(), active_runs.clone()),
+            move |message, delay| {
+                on_crash.write_or_recover().loading_state = LoadingState::crashed(message, delay);
+            },
+        );
+        state.task = Some(task.abort_handle());
+    }
+
+    /// Hide the popup and stop refreshing it, returns whether it was shown.
+    pub fn hide(&self) -> bool {
+        let mut state = self.state.write_or_recover();
+        if let Some(task) = state.task.take() {
+            task.abort();
+        }
+
+        state.view.take().is_some()
+    }
+
+    pub fn scroll_by(&self, rows: isize) {
+        let mut state = self.state.write_or_recover();
+        let count = match state.view {
+            Some(RunnersView::Runners) => state.runners.len(),
+            Some(RunnersView::Queue) => state.queued_jobs.len(),
+            None => return,
+        };
+
+        let idx = state
+            .table_state
+            .selected()
+            .map_or(0, |i| i.saturating_add_signed(rows));
+        state
+            .table_state
+            .select((count > 0).then(|| idx.min(count - 1)));
+    }
+
+    pub fn page_size(&self) -> isize {
+        self.state.read_or_recover().page_size.max(1) as isize
+    }
+
+    async fn sync_data(self, scopes: Vec<RunnerScope>, active_runs: ActiveRuns) {
+        // The task is aborted when the popup is hidden.
+        loop {
+            let (runners, failed_scopes) = self.fetch_runners(&scopes).await;
+            let queued_jobs = self.fetch_queued_jobs(active_runs()).await;
+
+            {
+                let mut state = self.state.write_or_recover();
+                state.runners = runners;
+                state.failed_scopes = failed_scopes;
+                state.queued_jobs = queued_jobs;
+                state.loading_state = LoadingState::Loaded(chrono::Local::now());
+            }
+
+            time::sleep(self.refresh_policy.interval(None, true)).await;
+        }
+    }
+
+    /// The runners of every scope, online first. Listing the runners needs
+    /// the admin permission, the scopes that fail are reported rather than
+    /// failing the whole view.
+    async fn fetch_runners(&self, scopes: &[RunnerScope]) -> (Vec<Runner>, Vec<RunnerScope>) {
+        let mut set = JoinSet::new();
+
+        for scope in scopes {
+            let github_service = self.github_service.clone();
+            let scope = scope.clone();
+            set.spawn(async move {
+                let runners = github_service.list_runners(&scope).await;
+                (scope, runners)
+            });
+        }
+
+        let mut runners = vec![];
+        let mut failed_scopes = vec![];
+
+        while let Some(res) = set.join_next().await {
+            match res {
+                Ok((_, Ok(r))) => runners.extend(r),
+                Ok((scope, Err(err))) => {
+                    error!("Failed to get the runners: {:?}", err);
+                    failed_scopes.push(scope);
+                }
+                Err(err) => error!("Failed to get the runners: {:?}", err),
+            }
+        }
+
+        runners.sort_by_key(|r| (!r.online, r.scope.to_string(), r.name.clone()));
+
+        (runners, failed_scopes)
+    }
+
+    /// The queued jobs of the runs still going, the longest waiting first.
+    async fn fetch_queued_jobs(&self, runs: Vec<WorkflowRun>) -> Vec<QueuedJob> {
+        let mut set = JoinSet::new();
+
+        for run in runs {
+            let github_service = self.github_service.clone();
+            set.spawn(async move {
+                let jobs = github_service.list_jobs(&run).await;
+                (run, jobs)
+            });
+        }
+
+        let mut queued_jobs = vec![];
+
+        while let Some(res) = set.join_next().await {
+            match res {
+                Ok((run, Ok(jobs))) => queued_jobs.extend(
+                    jobs.into_iter()
+                        .filter(|j| matches!(j.status, WorkflowJobStatus::Queued))
+                        .map(|job| QueuedJob {
+                            repo: format!("{}/{}", run.owner, run.repo),
+                            workflow: run.name.clone(),
+                            job,
+                        }),
+                ),
+                Ok((_, Err(err))) => error!("Failed to get the queued jobs: {:?}", err),
+                Err(err) => error!("Failed to get the queued jobs: {:?}", err),
+            }
+        }
+
+        queued_jobs.sort_by_key(|q| q.job.created_at);
+        queued_jobs
+    }
+
+    /// The runners that could pick a job: none online is starvation, all
+    /// busy is a queue.
+    fn runners_cell<'a>(&self, job: &WorkflowJob, runners: &[Runner]) -> Cell<'a> {
+        if !job.is_self_hosted() {
+            return Cell::from("GitHub-hosted");
+        }
+
+        let matching: Vec<_> = runners
+            .iter()
+            .filter(|r| r.online && r.can_run(job))
+            .collect();
+        let idle = matching.iter().filter(|r| !r.busy).count();
+
+        match (matching.len(), idle) {
+            (0, _) => Cell::from("none online").style(self.theme.failure_style()),
+            (n, 0) => Cell::from(format!("{} busy", n)).style(self.theme.pending_style()),
+            (n, idle) => Cell::from(format!("{} idle of {}", idle, n)),
+        }
+    }
+
+    fn runners_table<'a>(&self, state: &RunnersState) -> Table<'a> {
+        let online = state.runners.iter().filter(|r| r.online).count();
+        let busy = state.runners.iter().filter(|r| r.busy).count();
+        let title = format!(
+            "Runners ({} online, {} busy, {} offline)",
+            online,
+            busy,
+            state.runners.len() - online
+        );
+
+        let rows = state.runners.iter().map(|r| {
+            let status = match r.online {
+                true => Cell::from(format!("{} online", self.theme.symbols.success))
+                    .style(self.theme.success_style()),
+                false => Cell::from(format!("{} offline", self.theme.symbols.failure))
+                    .style(self.theme.failure_style()),
+            };
+            let activity = match (r.online, r.busy) {
+                (_, true) => Cell::from("busy").style(self.theme.running_style()),
+                (true, false) => Cell::from("idle"),
+                (false, false) => Cell::from(""),
+            };
+
+            Row::new(vec![
+                Cell::from(r.name.clone()),
+                Cell::from(r.scope.to_string()),
+                Cell::from(r.os.clone()),
+                status,
+                activity,
+                Cell::from(r.labels.join(", ")),
+            ])
+        });
+        let header = Row::new(vec!["Name", "Scope", "OS", "Status", "Activity", "Labels"])
+            .style(self.theme.header);
+        let widths = [
+            Constraint::Max(32),
+            Constraint::Max(32),
+            Constraint::Length(8),
+            Constraint::Length(12),
+            Constraint::Length(8),
+            Constraint::Fill(1),
+        ];
+
+        Table::new(rows, widths)
+            .header(header)
+            .block(self.block(state, title))
+    }
+
+    fn queue_table<'a>(&self, state: &RunnersState) -> Table<'a> {
+        let title = format!("Queue ({} jobs)", state.queued_jobs.len());
+        let now = Utc::now();
+
+        let rows = state.queued_jobs.iter().map(|q| {
+            Row::new(vec![
+                Cell::from(q.repo.clone()),
+                Cell::from(q.workflow.clone()),
+                Cell::from(q.job.name.clone()),
+                Cell::from(q.job.labels.join(", ")),
+                Cell::from(format_duration(now - q.job.created_at)),
+                self.runners_cell(&q.job, &state.runners),
+            ])
+        });
+        let header = Row::new(vec![
+            "Repository",
+            "Workflow",
+            "Job",
+            "Labels",
+            "Waiting",
+            "Runners",
+        ])
+        .style(self.theme.header);
+        let widths = [
+            Constraint::Max(32),
+            Constraint::Max(32),
+            Constraint::Fill(1),
+            Constraint::Max(40),
+            Constraint::Length(12),
+            Constraint::Length(14),
+        ];
+
+        Table::new(rows, widths)
+            .header(header)
+            .block(self.block(state, title))
+    }
+
+    fn block<'a>(&self, state: &RunnersState, title: String) -> Block<'a> {
+        let loading_state = Line::from(Span::styled(
+            match &state.loading_state {
+                LoadingState::Loading => "Loading...".to_string(),
+                LoadingState::Error(err) => err.clone(),
+                _ => String::new(),
+            },
+            self.theme.loading_state(&state.loading_state),
+        ))
+        .right_aligned();
+
+        let mut block = Block::bordered()
+            .title(title)
+            .title(loading_state)
+            .title_bottom(self.close_hint.clone());
+        if !state.failed_scopes.is_empty() {
+            let scopes: Vec<_> = state.failed_scopes.iter().map(|s| s.to_string()).collect();
+            block = block.title_bottom(
+                Line::from(format!(
+                    "Unable to list the runners of {}",
+                    scopes.join(", ")
+                ))
+                .style(self.theme.failure_style())
+                .right_aligned(),
+            );
+        }
+
+        block
+    }
+}
+
+impl Widget for &RunnersWidget {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let mut state = self.state.write_or_recover();
+
+        let (table, empty) = match state.view {
+            Some(RunnersView::Runners) => (
+                self.runners_table(&state),
+                state.runners.is_empty().then_some("No self-hosted runner"),
+            ),
+            Some(RunnersView::Queue) => (
+                self.queue_table(&state),
+                state
+                    .queued_jobs
+                    .is_empty()
+                    .then_some("No job is waiting for a runner"),
+            ),
+            None => return,
+        };
+
+        // The header and the borders take 4 lines.
+        state.page_size = usize::from(area.height.saturating_sub(4));
+
+        if let Some(text) = empty.filter(|_| state.loading_state != LoadingState::Loading) {
+            let title = match state.view {
+                Some(RunnersView::Runners) => "Runners",
+                _ => "Queue",
+            };
+            Paragraph::new(text)
+                .block(self.block(&state, title.to_string()))
+                .render(area, buf);
+            return;
+        }
+
+        let table = table.row_highlight_style(self.theme.highlight);
+        StatefulWidget::render(table, area, buf, &mut state.table_state);
+    }
+}
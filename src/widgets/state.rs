@@ -1,4 +1,5 @@
-use std::fmt::{Display, Formatter};
+use std::sync::{PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::time::Duration;
 
 #[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub(crate) enum LoadingState {
@@ -6,16 +7,41 @@ pub(crate) enum LoadingState {
     Idle,
     Loading,
     Loaded(chrono::DateTime<chrono::Local>),
+    /// The data couldn't be refreshed, the last known data is displayed.
+    Stale {
+        refreshed_at: chrono::DateTime<chrono::Local>,
+        retry_at: chrono::DateTime<chrono::Local>,
+    },
     Error(String),
 }
 
-impl Display for LoadingState {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        match self {
-            LoadingState::Loaded(time) => {
-                write!(f, "Last refreshed at {}", time.format("%Y-%m-%d %H:%M:%S"))
-            }
-            _ => write!(f, "{:?}", self),
-        }
+impl LoadingState {
+    /// The state of a background task that crashed, until it is restarted.
+    pub fn crashed(message: &str, restart_in: Duration) -> Self {
+        Self::Error(format!(
+            "Crashed: {}, restarting in {}s",
+            message,
+            restart_in.as_secs()
+        ))
+    }
+}
+
+/// Access to the state shared with the background tasks.
+///
+/// A task that panics while holding the lock poisons it. The state only holds
+/// data to display, which the restarted task refreshes, so the lock is
+/// recovered rather than propagating the panic to the UI.
+pub(crate) trait RwLockExt<T> {
+    fn read_or_recover(&self) -> RwLockReadGuard<'_, T>;
+    fn write_or_recover(&self) -> RwLockWriteGuard<'_, T>;
+}
+
+impl<T> RwLockExt<T> for RwLock<T> {
+    fn read_or_recover(&self) -> RwLockReadGuard<'_, T> {
+        self.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn write_or_recover(&self) -> RwLockWriteGuard<'_, T> {
+        self.write().unwrap_or_else(PoisonError::into_inner)
     }
 }
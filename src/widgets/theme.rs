@@ -0,0 +1,365 @@
+use log::warn;
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Span, Text};
+use ratatui::widgets::Cell;
+
+use crate::configuration::{CustomTheme, StyleSetting, SymbolSet, ThemeSettings};
+use crate::models::{
+    AnnotationLevel,
+    WorkflowJobConclusion,
+    WorkflowJobStatus,
+    WorkflowRun,
+    WorkflowRunConclusion,
+    WorkflowRunStatus,
+};
+use crate::widgets::state::LoadingState;
+
+/// How a status or a conclusion is coloured.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Outcome {
+    Success,
+    Failure,
+    Cancelled,
+    Skipped,
+    Pending,
+    Running,
+    Neutral,
+}
+
+/// The symbols displayed before the statuses and in the tables.
+#[derive(Debug, Clone)]
+pub struct Symbols {
+    pub pending: &'static str,
+    pub success: &'static str,
+    pub failure: &'static str,
+    pub cancelled: &'static str,
+    pub skipped: &'static str,
+    pub timed_out: &'static str,
+    pub warning: &'static str,
+    pub notice: &'static str,
+    /// Marks the rows being refreshed.
+    pub loading: &'static str,
+}
+
+impl Symbols {
+    const ASCII: Symbols = Symbols {
+        pending: "[.]",
+        success: "[+]",
+        failure: "[x]",
+        cancelled: "[-]",
+        skipped: "[>]",
+        timed_out: "[t]",
+        warning: "[!]",
+        notice: "[i]",
+        loading: "*",
+    };
+    const EMOJI: Symbols = Symbols {
+        pending: "⌛",
+        success: "✅",
+        failure: "❌",
+        cancelled: "🛑",
+        skipped: "⏩",
+        timed_out: "⏱️",
+        warning: "⚠️",
+        notice: "ℹ️",
+        loading: "⟳",
+    };
+}
+
+impl From<SymbolSet> for Symbols {
+    fn from(value: SymbolSet) -> Self {
+        match value {
+            SymbolSet::Emoji => Self::EMOJI,
+            SymbolSet::Ascii => Self::ASCII,
+        }
+    }
+}
+
+/// The styles and symbols of the widgets.
+#[derive(Debug, Clone)]
+pub struct Theme {
+    pub title: Style,
+    pub header: Style,
+    pub highlight: Style,
+    success: Style,
+    failure: Style,
+    cancelled: Style,
+    skipped: Style,
+    pending: Style,
+    running: Style,
+    neutral: Style,
+    pub symbols: Symbols,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+impl Theme {
+    /// The theme named in the settings, the dark theme if there is none with
+    /// that name.
+    pub fn new(settings: &ThemeSettings) -> Self {
+        let theme = match settings.custom.get(&settings.name) {
+            Some(custom) => Self::custom(custom),
+            None => Self::builtin(&settings.name).unwrap_or_else(|| {
+                warn!("Unknown theme {}, using the dark theme", settings.name);
+                Self::dark()
+            }),
+        };
+
+        Self {
+            symbols: settings.symbols.into(),
+            ..theme
+        }
+    }
+
+    fn builtin(name: &str) -> Option<Self> {
+        match name {
+            "dark" => Some(Self::dark()),
+            "light" => Some(Self::light()),
+            "high-contrast" => Some(Self::high_contrast()),
+            "colour-blind" | "color-blind" => Some(Self::colour_blind()),
+            _ => None,
+        }
+    }
+
+    fn custom(custom: &CustomTheme) -> Self {
+        let base = custom.base.as_deref().unwrap_or("dark");
+        let theme = Self::builtin(base).unwrap_or_else(|| {
+            warn!("Unknown base theme {}, using the dark theme", base);
+            Self::dark()
+        });
+
+        let pick = |style: Option<StyleSetting>, default: Style| style.map_or(default, |s| s.0);
+
+        Self {
+            title: pick(custom.title, theme.title),
+            header: pick(custom.header, theme.header),
+            highlight: pick(custom.highlight, theme.highlight),
+            success: pick(custom.success, theme.success),
+            failure: pick(custom.failure, theme.failure),
+            cancelled: pick(custom.cancelled, theme.cancelled),
+            skipped: pick(custom.skipped, theme.skipped),
+            pending: pick(custom.pending, theme.pending),
+            running: pick(custom.running, theme.running),
+            neutral: pick(custom.neutral, theme.neutral),
+            symbols: theme.symbols,
+        }
+    }
+
+    fn dark() -> Self {
+        Self {
+            title: Style::new().bold(),
+            header: Style::new().bold(),
+            highlight: Style::new().on_blue(),
+            success: Style::new().green(),
+            failure: Style::new().red(),
+            cancelled: Style::new().dark_gray(),
+            skipped: Style::new().dark_gray(),
+            pending: Style::new().yellow(),
+            running: Style::new().cyan(),
+            neutral: Style::new(),
+            symbols: Symbols::EMOJI,
+        }
+    }
+
+    fn light() -> Self {
+        Self {
+            title: Style::new().bold(),
+            header: Style::new().bold(),
+            highlight: Style::new().black().on_light_cyan(),
+            success: Style::new().green(),
+            failure: Style::new().red(),
+            cancelled: Style::new().gray(),
+            skipped: Style::new().gray(),
+            // Yellow is hard to read on a light background.
+            pending: Style::new().fg(Color::Indexed(130)),
+            running: Style::new().blue(),
+            neutral: Style::new(),
+            symbols: Symbols::EMOJI,
+        }
+    }
+
+    fn high_contrast() -> Self {
+        Self {
+            title: Style::new().bold().underlined(),
+            header: Style::new().bold().underlined(),
+            highlight: Style::new().black().on_white().bold(),
+            success: Style::new().light_green().bold(),
+            failure: Style::new().white().on_red().bold(),
+            cancelled: Style::new().white().add_modifier(Modifier::ITALIC),
+            skipped: Style::new().white().add_modifier(Modifier::ITALIC),
+            pending: Style::new().light_yellow().bold(),
+            running: Style::new().light_cyan().bold(),
+            neutral: Style::new().white(),
+            symbols: Symbols::EMOJI,
+        }
+    }
+
+    /// Based on the Okabe-Ito palette, the success and the failure are blue
+    /// and orange rather than green and red.
+    fn colour_blind() -> Self {
+        Self {
+            title: Style::new().bold(),
+            header: Style::new().bold(),
+            highlight: Style::new().on_dark_gray(),
+            success: Style::new().fg(Color::Rgb(0, 114, 178)),
+            failure: Style::new().fg(Color::Rgb(213, 94, 0)).bold(),
+            cancelled: Style::new().fg(Color::Rgb(204, 121, 167)),
+            skipped: Style::new().gray(),
+            pending: Style::new().fg(Color::Rgb(240, 228, 66)),
+            running: Style::new().fg(Color::Rgb(86, 180, 233)),
+            neutral: Style::new(),
+            symbols: Symbols::EMOJI,
+        }
+    }
+
+    fn style(&self, outcome: Outcome) -> Style {
+        match outcome {
+            Outcome::Success => self.success,
+            Outcome::Failure => self.failure,
+            Outcome::Cancelled => self.cancelled,
+            Outcome::Skipped => self.skipped,
+            Outcome::Pending => self.pending,
+            Outcome::Running => self.running,
+            Outcome::Neutral => self.neutral,
+        }
+    }
+
+    fn cell<'a>(&self, symbol: Option<&str>, label: String, outcome: Outcome) -> Cell<'a> {
+        let text = match symbol {
+            Some(symbol) => format!("{} {}", symbol, label),
+            None => label,
+        };
+
+        Cell::from(Text::from(text)).style(self.style(outcome))
+    }
+
+    fn run_status_outcome(status: &WorkflowRunStatus) -> Outcome {
+        match status {
+            WorkflowRunStatus::Queued => Outcome::Pending,
+            WorkflowRunStatus::InProgress => Outcome::Running,
+            WorkflowRunStatus::Completed | WorkflowRunStatus::Other(_) => Outcome::Neutral,
+        }
+    }
+
+    fn run_conclusion_parts(
+        &self,
+        conclusion: &WorkflowRunConclusion,
+    ) -> (Option<&'static str>, Outcome) {
+        match conclusion {
+            WorkflowRunConclusion::Pending => (Some(self.symbols.pending), Outcome::Pending),
+            WorkflowRunConclusion::Success => (Some(self.symbols.success), Outcome::Success),
+            WorkflowRunConclusion::Failure => (Some(self.symbols.failure), Outcome::Failure),
+            WorkflowRunConclusion::Other(c) => match c.as_str() {
+                "cancelled" => (Some(self.symbols.cancelled), Outcome::Cancelled),
+                "skipped" => (Some(self.symbols.skipped), Outcome::Skipped),
+                "timed_out" => (Some(self.symbols.timed_out), Outcome::Failure),
+                _ => (None, Outcome::Neutral),
+            },
+        }
+    }
+
+    pub fn run_status<'a>(&self, status: &WorkflowRunStatus) -> Cell<'a> {
+        self.cell(None, status.to_string(), Self::run_status_outcome(status))
+    }
+
+    /// The conclusion of a run, only its symbol if `compact`.
+    pub fn run_conclusion<'a>(
+        &self,
+        conclusion: &WorkflowRunConclusion,
+        compact: bool,
+    ) -> Cell<'a> {
+        let (symbol, outcome) = self.run_conclusion_parts(conclusion);
+
+        match (symbol, compact) {
+            (Some(symbol), true) => self.cell(None, symbol.to_string(), outcome),
+            _ => self.cell(symbol, conclusion.to_string(), outcome),
+        }
+    }
+
+    /// The symbol of the conclusion of a run, styled like the run.
+    pub fn run_symbol<'a>(&self, run: &WorkflowRun) -> Span<'a> {
+        let (symbol, _) = self.run_conclusion_parts(&run.conclusion);
+
+        Span::styled(symbol.unwrap_or("-"), self.run_style(run))
+    }
+
+    /// The style of a run: the style of its status while it isn't completed,
+    /// the style of its conclusion afterwards.
+    pub fn run_style(&self, run: &WorkflowRun) -> Style {
+        let outcome = match run.is_completed() {
+            true => self.run_conclusion_parts(&run.conclusion).1,
+            false => Self::run_status_outcome(&run.status),
+        };
+
+        self.style(outcome)
+    }
+
+    pub fn job_status<'a>(&self, status: &WorkflowJobStatus) -> Cell<'a> {
+        let outcome = match status {
+            WorkflowJobStatus::Pending | WorkflowJobStatus::Queued => Outcome::Pending,
+            WorkflowJobStatus::InProgress => Outcome::Running,
+            WorkflowJobStatus::Failed => Outcome::Failure,
+            WorkflowJobStatus::Completed | WorkflowJobStatus::Other(_) => Outcome::Neutral,
+        };
+
+        self.cell(None, status.to_string(), outcome)
+    }
+
+    pub fn job_conclusion<'a>(&self, conclusion: &WorkflowJobConclusion) -> Cell<'a> {
+        let (symbol, outcome) = match conclusion {
+            WorkflowJobConclusion::Success => (Some(self.symbols.success), Outcome::Success),
+            WorkflowJobConclusion::Failure => (Some(self.symbols.failure), Outcome::Failure),
+            WorkflowJobConclusion::Cancelled => (Some(self.symbols.cancelled), Outcome::Cancelled),
+            WorkflowJobConclusion::Skipped => (Some(self.symbols.skipped), Outcome::Skipped),
+            WorkflowJobConclusion::TimedOut => (Some(self.symbols.timed_out), Outcome::Failure),
+            WorkflowJobConclusion::ActionRequired => (None, Outcome::Pending),
+            WorkflowJobConclusion::Neutral | WorkflowJobConclusion::Other(_) => {
+                (None, Outcome::Neutral)
+            }
+        };
+
+        self.cell(symbol, conclusion.to_string(), outcome)
+    }
+
+    /// The symbol and the style of the annotations of a level.
+    pub fn annotation(&self, level: AnnotationLevel) -> (&'static str, Style) {
+        match level {
+            AnnotationLevel::Failure => (self.symbols.failure, self.failure),
+            AnnotationLevel::Warning => (self.symbols.warning, self.pending),
+            AnnotationLevel::Notice => (self.symbols.notice, self.neutral),
+        }
+    }
+
+    pub fn success_style(&self) -> Style {
+        self.success
+    }
+
+    pub fn failure_style(&self) -> Style {
+        self.failure
+    }
+
+    pub fn pending_style(&self) -> Style {
+        self.pending
+    }
+
+    pub fn running_style(&self) -> Style {
+        self.running
+    }
+
+    pub fn skipped_style(&self) -> Style {
+        self.skipped
+    }
+
+    /// The style of the loading state in the title of the tables.
+    pub fn loading_state(&self, state: &LoadingState) -> Style {
+        match state {
+            LoadingState::Error(_) => self.failure,
+            LoadingState::Stale { .. } => self.pending,
+            _ => self.neutral,
+        }
+    }
+}
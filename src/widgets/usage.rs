@@ -0,0 +1,352 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock};
+
+use chrono::{DateTime, Utc};
+use exn::Result;
+use log::error;
+use ratatui::buffer::Buffer;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Cell, Paragraph, Row, StatefulWidget, Table, TableState, Widget};
+use tokio::task::{AbortHandle, JoinSet};
+
+use crate::error::ServiceError;
+use crate::history::HistoryStore;
+use crate::models::{RunnerOs, WorkflowRun};
+use crate::service::workflows::{GitHubService, Service};
+use crate::supervisor::supervise;
+use crate::usage;
+use crate::usage::WorkflowUsage;
+use crate::widgets::state::{LoadingState, RwLockExt};
+use crate::widgets::theme::Theme;
+
+#[derive(Debug, Default)]
+struct UsageState {
+    visible: bool,
+    usages: Vec<WorkflowUsage>,
+    /// The workflows whose usage couldn't be reported nor estimated.
+    failed_workflows: usize,
+    loading_state: LoadingState,
+    table_state: TableState,
+    page_size: usize,
+    /// The background task fetching the usage.
+    task: Option<AbortHandle>,
+}
+
+/// The billable Actions minutes of the workflows in the current billing
+/// period, shown over the runs, the most expensive workflows first.
+///
+/// The minutes are reported by GitHub, or estimated from the jobs in the
+/// history when GitHub reports none.
+#[derive(Debug, Clone)]
+pub(crate) struct UsageWidget {
+    github_service: Arc<dyn GitHubService>,
+    history: Option<Arc<HistoryStore>>,
+    state: Arc<RwLock<UsageState>>,
+    theme: Theme,
+    /// The hint at the bottom of the popup.
+    close_hint: String,
+}
+
+impl Default for UsageWidget {
+    fn default() -> Self {
+        Self {
+            github_service: Arc::new(Service {}),
+            history: None,
+            state: Arc::new(RwLock::new(UsageState::default())),
+            theme: Theme::default(),
+            close_hint: String::new(),
+        }
+    }
+}
+
+impl UsageWidget {
+    pub fn new(
+        github_service: Arc<dyn GitHubService>,
+        history: Option<Arc<HistoryStore>>,
+        theme: Theme,
+        close_hint: String,
+    ) -> Self {
+        Self {
+            github_service,
+            history,
+            theme,
+            close_hint,
+            ..Default::default()
+        }
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.state.read_or_recover().visible
+    }
+
+    /// Show the usage of the workflows of `runs`, one run per workflow, and
+    /// fetch it again if it is already shown.
+    pub fn show(&self, runs: Vec<WorkflowRun>) {
+        let mut state = self.state.write_or_recover();
+        if let Some(task) = state.task.take() {
+            task.abort();
+        }
+        state.visible = true;
+        state.loading_state = LoadingState::Loading;
+
+        let this = self.clone();
+        let on_crash = self.state.clone();
+        let task = supervise(
+            "usage",
+            move || this.clone().sync_data(runs.clone()),
+            move |message, delay| {
+                on_crash.write_or_recover().loading_state = LoadingState::crashed(message, delay);
+            },
+        );
+        state.task = Some(task.abort_handle());
+    }
+
+    /// Hide the popup and stop fetching the usage, returns whether it was
+    /// shown.
+    pub fn hide(&self) -> bool {
+        let mut state = self.state.write_or_recover();
+        if let Some(task) = state.task.take() {
+            task.abort();
+        }
+
+        std::mem::take(&mut state.visible)
+    }
+
+    pub fn scroll_by(&self, rows: isize) {
+        let mut state = self.state.write_or_recover();
+        let count = state.usages.len();
+
+        let idx = state
+            .table_state
+            .selected()
+            .map_or(0, |i| i.saturating_add_signed(rows));
+        state
+            .table_state
+            .select((count > 0).then(|| idx.min(count - 1)));
+    }
+
+    pub fn page_size(&self) -> isize {
+        self.state.read_or_recover().page_size.max(1) as isize
+    }
+
+    async fn sync_data(self, runs: Vec<WorkflowRun>) {
+        let since = usage::billing_period_start(Utc::now());
+        let mut set = JoinSet::new();
+
+        for run in runs {
+            let github_service = self.github_service.clone();
+            set.spawn(async move {
+                let billable_ms = github_service.get_workflow_usage(&run).await;
+                (run, billable_ms)
+            });
+        }
+
+        let mut usages = vec![];
+        let mut failed_workflows = 0;
+
+        while let Some(res) = set.join_next().await {
+            let (run, billable_ms) = match res {
+                Ok(r) => r,
+                Err(err) => {
+                    error!("Failed to get the usage of a workflow: {:?}", err);
+                    failed_workflows += 1;
+                    continue;
+                }
+            };
+
+            match self.workflow_usage(&run, billable_ms, since).await {
+                Some(u) => usages.push(u),
+                None => failed_workflows += 1,
+            }
+        }
+
+        usage::rank(&mut usages);
+
+        let mut state = self.state.write_or_recover();
+        state.table_state = TableState::default().with_selected((!usages.is_empty()).then_some(0));
+        state.usages = usages;
+        state.failed_workflows = failed_workflows;
+        state.loading_state = LoadingState::Loaded(chrono::Local::now());
+        state.task = None;
+    }
+
+    /// The usage GitHub reports for the workflow of `run`. When it reports
+    /// none, e.g. once the timing endpoint is retired, the usage is estimated
+    /// from the jobs in the history.
+    async fn workflow_usage(
+        &self,
+        run: &WorkflowRun,
+        billable_ms: Result<BTreeMap<RunnerOs, u64>, ServiceError>,
+        since: DateTime<Utc>,
+    ) -> Option<WorkflowUsage> {
+        let billable_ms = match billable_ms {
+            Ok(ms) if !ms.is_empty() => Some(ms),
+            Ok(_) => None,
+            Err(err) => {
+                error!("Failed to get the usage of {}: {:?}", run.name, err);
+                // Without the history, there is nothing to estimate it from.
+                self.history.as_ref()?;
+                None
+            }
+        };
+
+        let (billable_ms, estimated) = match (billable_ms, &self.history) {
+            (Some(ms), _) => (ms, false),
+            (None, Some(history)) => {
+                let workflow = run.clone();
+                let jobs = history.run_blocking(move |h| h.workflow_jobs_since(&workflow, since));
+
+                match jobs.await {
+                    Ok(jobs) => (usage::estimate(&jobs, since), true),
+                    Err(err) => {
+                        error!("Failed to load the jobs of {}: {:?}", run.name, err);
+                        return None;
+                    }
+                }
+            }
+            (None, None) => (BTreeMap::new(), false),
+        };
+
+        Some(WorkflowUsage {
+            repo: format!("{}/{}", run.owner, run.repo),
+            workflow: run.name.clone(),
+            billable_ms,
+            estimated,
+        })
+    }
+
+    /// The minutes per OS and the most expensive repositories.
+    fn summary<'a>(&self, usages: &[WorkflowUsage]) -> Vec<Line<'a>> {
+        let per_os = usage::minutes_per_os(usages);
+        let weighted: u64 = usages.iter().map(WorkflowUsage::weighted_minutes).sum();
+        let per_os: Vec<_> = RunnerOs::ALL
+            .iter()
+            .map(|os| format!("{} {} min", os, per_os.get(os).copied().unwrap_or(0)))
+            .collect();
+
+        let repos: Vec<_> = usage::weighted_minutes_per_repo(usages)
+            .into_iter()
+            .take(5)
+            .map(|(repo, minutes)| format!("{} {} min", repo, minutes))
+            .collect();
+
+        vec![
+            Line::from(vec![
+                Span::styled(format!("{} weighted min", weighted), self.theme.header),
+                Span::raw(format!(" ({})", per_os.join(", "))),
+            ]),
+            Line::from(format!("Top repositories: {}", repos.join(", "))),
+        ]
+    }
+
+    fn table<'a>(&self, usages: &[WorkflowUsage]) -> Table<'a> {
+        let rows = usages.iter().map(|u| {
+            let source = match u.estimated {
+                true => Cell::from("estimated").style(self.theme.skipped_style()),
+                false => Cell::from("billed"),
+            };
+
+            let mut cells = vec![Cell::from(u.repo.clone()), Cell::from(u.workflow.clone())];
+            cells.extend(
+                RunnerOs::ALL
+                    .iter()
+                    .map(|os| Cell::from(u.minutes(*os).to_string())),
+            );
+            cells.extend([
+                Cell::from(u.total_minutes().to_string()),
+                Cell::from(u.weighted_minutes().to_string()),
+                source,
+            ]);
+
+            Row::new(cells)
+        });
+
+        let mut header = vec!["Repository".to_string(), "Workflow".to_string()];
+        header.extend(RunnerOs::ALL.iter().map(|os| os.to_string()));
+        header.extend([
+            "Total".to_string(),
+            "Weighted".to_string(),
+            "Source".to_string(),
+        ]);
+        let header = Row::new(header).style(self.theme.header);
+
+        let widths = [
+            Constraint::Max(32),
+            Constraint::Fill(1),
+            Constraint::Length(8),
+            Constraint::Length(8),
+            Constraint::Length(8),
+            Constraint::Length(8),
+            Constraint::Length(9),
+            Constraint::Length(10),
+        ];
+
+        Table::new(rows, widths)
+            .header(header)
+            .row_highlight_style(self.theme.highlight)
+    }
+
+    fn block<'a>(&self, state: &UsageState) -> Block<'a> {
+        let since = usage::billing_period_start(Utc::now());
+        let loading_state = Line::from(Span::styled(
+            match &state.loading_state {
+                LoadingState::Loading => "Loading...".to_string(),
+                LoadingState::Error(err) => err.clone(),
+                _ => String::new(),
+            },
+            self.theme.loading_state(&state.loading_state),
+        ))
+        .right_aligned();
+
+        let mut block = Block::bordered()
+            .title(format!(
+                "Actions Minutes since {} (Linux x1, Windows x2, macOS x10)",
+                since.format("%Y-%m-%d")
+            ))
+            .title(loading_state)
+            .title_bottom(self.close_hint.clone());
+        if state.failed_workflows > 0 {
+            block = block.title_bottom(
+                Line::from(format!(
+                    "Unable to get the usage of {} workflows",
+                    state.failed_workflows
+                ))
+                .style(self.theme.failure_style())
+                .right_aligned(),
+            );
+        }
+
+        block
+    }
+}
+
+impl Widget for &UsageWidget {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let mut state = self.state.write_or_recover();
+        if !state.visible {
+            return;
+        }
+
+        let block = self.block(&state);
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        if state.usages.is_empty() {
+            if state.loading_state != LoadingState::Loading {
+                Paragraph::new("No workflow in the runs").render(inner, buf);
+            }
+            return;
+        }
+
+        let layout = Layout::vertical([Constraint::Length(3), Constraint::Fill(1)]);
+        let [summary_area, table_area] = inner.layout(&layout);
+        Paragraph::new(self.summary(&state.usages)).render(summary_area, buf);
+
+        // The header takes a line.
+        state.page_size = usize::from(table_area.height.saturating_sub(1));
+
+        let table = self.table(&state.usages);
+        StatefulWidget::render(table, table_area, buf, &mut state.table_state);
+    }
+}
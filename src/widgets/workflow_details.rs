@@ -1,31 +1,283 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::PathBuf;
 use std::sync::{Arc, RwLock};
 use std::time::Duration;
 
+use crossterm::event::{Event, MouseButton, MouseEventKind};
 use exn::Exn;
+use log::error;
+use octocrab::models::{ArtifactId, JobId, WorkflowId};
 use ratatui::buffer::Buffer;
-use ratatui::layout::{Constraint, Rect};
-use ratatui::style::Style;
-use ratatui::text::Line;
-use ratatui::widgets::{Block, HighlightSpacing, Row, StatefulWidget, Table, TableState, Widget};
+use ratatui::layout::{Constraint, Layout, Position, Rect};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{
+    Block,
+    BorderType,
+    Cell,
+    HighlightSpacing,
+    Paragraph,
+    Row,
+    Sparkline,
+    StatefulWidget,
+    Table,
+    TableState,
+    Widget,
+};
+use tokio::sync::{Semaphore, watch};
+use tokio::task::{AbortHandle, JoinSet};
 use tokio::time;
 
-use crate::error::ServiceError;
-use crate::models::{WorkflowJob, WorkflowRun};
+use crate::browser::Browser;
+use crate::configuration::{ArtifactSettings, DetailsPlacement, Settings};
+use crate::error::{ServiceError, StorageError};
+use crate::history::HistoryStore;
+use crate::junit::TestReport;
+use crate::keymap::{Action, Keymap};
+use crate::models::{Artifact, CheckRun, WorkflowJob, WorkflowJobConclusion, WorkflowRun};
 use crate::service::workflows::{GitHubService, Service};
-use crate::widgets::state::LoadingState;
+use crate::stats::WorkflowStats;
+use crate::supervisor::supervise;
+use crate::widgets::format::{TimeFormatter, format_duration, format_size};
+use crate::widgets::mouse::row_at;
+use crate::widgets::refresh::RefreshPolicy;
+use crate::widgets::state::{LoadingState, RwLockExt};
+use crate::widgets::theme::Theme;
+use crate::{artifacts, clipboard, junit};
 
 #[derive(Debug, Default)]
 struct WorkflowDetailsState {
+    /// Incremented every time the details are shown or hidden, the updates of
+    /// the tasks of an older generation are discarded.
+    generation: u64,
+    /// The run whose details are shown.
+    workflow: Option<WorkflowRun>,
     workflow_jobs: Vec<WorkflowJob>,
+    /// The jobs of the attempts before the latest one, from the first.
+    earlier_attempts: Vec<(u64, Vec<WorkflowJob>)>,
+    check_runs: Vec<CheckRun>,
+    stats: Option<WorkflowStats>,
+    /// The statistics of the workflows shown before, with when they were
+    /// computed. They outlive the details.
+    stats_cache: HashMap<WorkflowId, (time::Instant, WorkflowStats)>,
+    /// Whether the statistics are only computed on demand.
+    stats_deferred: bool,
     loading_state: LoadingState,
+    /// The selected job, the index in `table_state` is derived from it after
+    /// every reload.
+    selected_job: Option<JobId>,
     table_state: TableState,
+    /// The area of the jobs table, as of the last render.
+    table_area: Rect,
+    page_size: usize,
+    /// Whether the keys go to the jobs.
+    focused: bool,
+    /// The outcome of the last action on a job.
+    notice: Option<String>,
+    /// What is shown below the statistics.
+    view: DetailsView,
+    /// The artifacts of the run, `None` until they are fetched.
+    artifacts: Option<Vec<Artifact>>,
+    artifact_table_state: TableState,
+    /// The downloads of this session, they go on when the details are closed.
+    downloads: HashMap<ArtifactId, Download>,
+    /// The JUnit reports found in the downloaded artifacts.
+    test_reports: HashMap<ArtifactId, TestReport>,
+    /// The first line of the test results shown.
+    test_scroll: u16,
+    /// The number of lines of the test results, as of the last render.
+    test_lines: usize,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum DetailsView {
+    #[default]
+    Jobs,
+    Artifacts,
+    TestResults,
+}
+
+#[derive(Debug, Clone)]
+enum Download {
+    InProgress { received: u64, total: u64 },
+    Extracting,
+    Done(PathBuf),
+    Failed(String),
+}
+
+impl WorkflowDetailsState {
+    /// Replace the jobs, keeping the selected job selected.
+    ///
+    /// If the job is gone, the job that took its place is selected.
+    fn set_jobs(&mut self, jobs: Vec<WorkflowJob>) {
+        self.workflow_jobs = jobs;
+
+        let idx = self
+            .selected_job
+            .and_then(|id| self.workflow_jobs.iter().position(|j| j.id == id))
+            .or(self.table_state.selected());
+
+        self.select(idx);
+    }
+
+    fn select(&mut self, idx: Option<usize>) {
+        let idx = idx.map(|i| i.min(self.workflow_jobs.len().saturating_sub(1)));
+        let job = idx.and_then(|i| self.workflow_jobs.get(i));
+
+        self.selected_job = job.map(|j| j.id);
+        self.table_state.select(idx.filter(|_| job.is_some()));
+    }
+
+    fn scroll_by(&mut self, rows: isize) {
+        let idx = self
+            .table_state
+            .selected()
+            .map_or(0, |i| i.saturating_add_signed(rows));
+        self.select(Some(idx));
+    }
+
+    /// Set the jobs of all the attempts, only the latest attempt is in the
+    /// table.
+    fn set_attempts(&mut self, jobs: Vec<WorkflowJob>) {
+        let latest = jobs.iter().map(|j| j.run_attempt).max().unwrap_or_default();
+
+        let mut attempts: BTreeMap<u64, Vec<WorkflowJob>> = BTreeMap::new();
+        for job in jobs {
+            attempts.entry(job.run_attempt).or_default().push(job);
+        }
+
+        let jobs = attempts.remove(&latest).unwrap_or_default();
+        self.earlier_attempts = attempts.into_iter().collect();
+        self.set_jobs(jobs);
+    }
+
+    /// Set the jobs of the attempts before `latest`, the jobs of the latest
+    /// attempt are kept as they are more recent.
+    fn set_earlier_attempts(&mut self, jobs: Vec<WorkflowJob>, latest: u64) {
+        let mut attempts: BTreeMap<u64, Vec<WorkflowJob>> = BTreeMap::new();
+        for job in jobs.into_iter().filter(|j| j.run_attempt < latest) {
+            attempts.entry(job.run_attempt).or_default().push(job);
+        }
+
+        self.earlier_attempts = attempts.into_iter().collect();
+    }
+
+    /// The attempt of the jobs in the table, when the run was re-run.
+    fn jobs_attempt(&self) -> Option<u64> {
+        self.workflow_jobs
+            .first()
+            .map(|j| j.run_attempt)
+            .filter(|a| *a > 1)
+    }
+
+    /// The check run of the selected job, or of the first job with
+    /// annotations when none is selected.
+    fn check_run(&self) -> Option<&CheckRun> {
+        match self.selected_job {
+            Some(id) => self.check_runs.iter().find(|c| c.job_id == id),
+            None => self.check_runs.iter().find(|c| !c.annotations.is_empty()),
+        }
+    }
+
+    fn select_artifact(&mut self, idx: usize) {
+        let count = self.artifacts.as_ref().map_or(0, Vec::len);
+        self.artifact_table_state
+            .select((count > 0).then(|| idx.min(count - 1)));
+    }
+
+    fn scroll_artifacts_by(&mut self, rows: isize) {
+        let idx = self
+            .artifact_table_state
+            .selected()
+            .map_or(0, |i| i.saturating_add_signed(rows));
+        self.select_artifact(idx);
+    }
+
+    fn selected_artifact(&self) -> Option<&Artifact> {
+        let idx = self.artifact_table_state.selected()?;
+        self.artifacts.as_ref()?.get(idx)
+    }
+
+    /// The test results of the artifacts of the run.
+    fn test_report(&self) -> Option<TestReport> {
+        self.artifacts
+            .iter()
+            .flatten()
+            .filter_map(|a| self.test_reports.get(&a.id))
+            .cloned()
+            .reduce(|mut report, other| {
+                report.merge(other);
+                report
+            })
+    }
+
+    /// The artifacts that look like test reports but are too large to be
+    /// downloaded for the test results, unless they were downloaded anyway.
+    fn large_test_artifacts(&self) -> usize {
+        self.artifacts
+            .iter()
+            .flatten()
+            .filter(|a| !a.expired && junit::is_test_artifact(&a.name))
+            .filter(|a| a.size_in_bytes > WorkflowDetailsWidget::MAX_TEST_REPORT_SIZE)
+            .filter(|a| !self.downloads.contains_key(&a.id))
+            .count()
+    }
+
+    /// Whether the test reports of the run are still being downloaded.
+    fn downloading_test_reports(&self) -> bool {
+        self.artifacts.iter().flatten().any(|a| {
+            junit::is_test_artifact(&a.name)
+                && matches!(
+                    self.downloads.get(&a.id),
+                    Some(Download::InProgress { .. } | Download::Extracting)
+                )
+        })
+    }
+
+    /// Scroll the test results to a line, without scrolling past their last
+    /// page.
+    fn scroll_tests_to(&mut self, line: usize) {
+        let max = self.test_lines.saturating_sub(self.page_size);
+        self.test_scroll = u16::try_from(line.min(max)).unwrap_or(u16::MAX);
+    }
+
+    fn scroll_tests_by(&mut self, lines: isize) {
+        let line = usize::from(self.test_scroll).saturating_add_signed(lines);
+        self.scroll_tests_to(line);
+    }
+
+    fn selected_job(&self) -> Option<&WorkflowJob> {
+        let id = self.selected_job?;
+        self.workflow_jobs.iter().find(|j| j.id == id)
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct WorkflowDetailsWidget {
     github_service: Arc<dyn GitHubService + Sync + Send>,
     state: Arc<RwLock<WorkflowDetailsState>>,
+    history: Option<Arc<HistoryStore>>,
+    time_formatter: TimeFormatter,
+    theme: Theme,
+    refresh_policy: RefreshPolicy,
+    /// The hint at the bottom of the jobs.
+    footer: String,
+    browser: Browser,
+    artifact_settings: ArtifactSettings,
+    /// The hint at the bottom of the artifacts.
+    artifacts_footer: String,
+    /// Shown instead of the statistics until they are computed on demand.
+    stats_hint: String,
+    /// How to download the test reports too large to be downloaded for the
+    /// test results.
+    large_artifacts_hint: String,
+    stats_runs: u8,
     visible: bool,
+    /// The background task fetching the details.
+    task: Option<AbortHandle>,
+    /// The generation of the state this widget updates, only meaningful in
+    /// the background task.
+    generation: u64,
 }
 
 impl Default for WorkflowDetailsWidget {
@@ -33,22 +285,107 @@ impl Default for WorkflowDetailsWidget {
         Self {
             github_service: Arc::new(Service {}),
             state: Arc::new(RwLock::new(WorkflowDetailsState::default())),
+            history: None,
+            time_formatter: TimeFormatter::default(),
+            theme: Theme::default(),
+            refresh_policy: RefreshPolicy::default(),
+            footer: String::new(),
+            browser: Browser::default(),
+            artifact_settings: ArtifactSettings::default(),
+            artifacts_footer: String::new(),
+            stats_hint: String::new(),
+            large_artifacts_hint: String::new(),
+            stats_runs: Settings::default().stats_runs,
             visible: false,
+            task: None,
+            generation: 0,
         }
     }
 }
 
 impl WorkflowDetailsWidget {
-    pub fn new(github_service: Arc<dyn GitHubService + Sync + Send>) -> Self {
+    const MAX_ATTEMPTS: usize = 3;
+    const MAX_CHECK_LINES: usize = 8;
+    /// The number of test reports downloaded at once.
+    const MAX_CONCURRENT_DOWNLOADS: usize = 2;
+    /// The largest artifact downloaded for the test results, the larger ones
+    /// likely hold binaries rather than reports.
+    const MAX_TEST_REPORT_SIZE: u64 = 20 * 1024 * 1024;
+    const ROW_HEIGHT: u16 = 2;
+    /// How long the statistics of a workflow are reused for.
+    const STATS_TTL: Duration = Duration::from_secs(10 * 60);
+
+    pub fn new(
+        github_service: Arc<dyn GitHubService + Sync + Send>,
+        config: &Settings,
+        history: Option<Arc<HistoryStore>>,
+        refresh_policy: RefreshPolicy,
+    ) -> Self {
         Self {
             github_service,
+            history,
+            refresh_policy,
+            time_formatter: TimeFormatter::new(&config.time),
+            theme: Theme::new(&config.theme),
+            // The details next to the runs can't be closed.
+            footer: match config.layout.details {
+                DetailsPlacement::Popup => Keymap::new(&config.keys)
+                    .hint(Action::Close, "to close")
+                    .unwrap_or_default(),
+                _ => String::new(),
+            },
+            stats_runs: config.stats_runs,
+            browser: Browser::new(&config.browser),
+            artifact_settings: config.artifacts.clone(),
+            artifacts_footer: Keymap::new(&config.keys)
+                .hint(Action::Download, "to download")
+                .unwrap_or_default(),
+            stats_hint: Keymap::new(&config.keys)
+                .hint(Action::Details, "to compute them")
+                .unwrap_or_default(),
+            large_artifacts_hint: Keymap::new(&config.keys)
+                .hint(Action::Download, "in the artifacts to download them")
+                .unwrap_or_default(),
             ..Default::default()
         }
     }
 
-    pub fn run(&mut self, workflow: WorkflowRun) {
-        let this = self.clone();
-        tokio::spawn(this.sync_data(workflow));
+    /// Start fetching the details of a run in the background, stopping the
+    /// task fetching the previous run. The statistics are only computed if
+    /// `fetch_stats`, or reused if they were computed recently.
+    pub fn run(&mut self, workflow: WorkflowRun, fetch_stats: bool) {
+        self.stop();
+
+        let mut this = self.clone();
+        {
+            let mut state = self.state.write_or_recover();
+            if state.workflow.as_ref().is_none_or(|w| w.id != workflow.id) {
+                state.notice = None;
+                state.view = DetailsView::Jobs;
+                state.artifacts = None;
+            }
+            state.workflow = Some(workflow.clone());
+            state.stats_deferred = !fetch_stats;
+            this.generation = state.generation;
+        }
+        let on_crash = this.clone();
+        let task = supervise(
+            "details",
+            move || this.clone().sync_data(workflow.clone(), fetch_stats),
+            move |message, delay| {
+                on_crash.update(|state| state.loading_state = LoadingState::crashed(message, delay))
+            },
+        );
+        self.task = Some(task.abort_handle());
+    }
+
+    /// Abort the background task, and discard its pending updates.
+    fn stop(&mut self) {
+        if let Some(task) = self.task.take() {
+            task.abort();
+        }
+
+        self.state.write_or_recover().generation += 1;
     }
 
     pub fn is_visible(&self) -> bool {
@@ -59,68 +396,918 @@ impl WorkflowDetailsWidget {
         self.visible = true;
     }
 
+    /// Select the clicked job, and scroll the jobs with the mouse wheel.
+    pub fn handle_event(&self, event: &Event) {
+        let Event::Mouse(mouse) = event else {
+            return;
+        };
+
+        let mut state = self.state.write_or_recover();
+
+        // Only the jobs can be clicked.
+        match (state.view, mouse.kind) {
+            (DetailsView::Jobs, _) => {}
+            (DetailsView::Artifacts, MouseEventKind::ScrollDown) => {
+                return state.scroll_artifacts_by(1);
+            }
+            (DetailsView::Artifacts, MouseEventKind::ScrollUp) => {
+                return state.scroll_artifacts_by(-1);
+            }
+            (DetailsView::TestResults, MouseEventKind::ScrollDown) => {
+                return state.scroll_tests_by(1);
+            }
+            (DetailsView::TestResults, MouseEventKind::ScrollUp) => {
+                return state.scroll_tests_by(-1);
+            }
+            _ => return,
+        }
+
+        match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                let position = Position::new(mouse.column, mouse.row);
+                let offset = state.table_state.offset();
+                if let Some(idx) = row_at(state.table_area, 1, offset, Self::ROW_HEIGHT, position)
+                    && idx < state.workflow_jobs.len()
+                {
+                    state.select(Some(idx));
+                }
+            }
+            MouseEventKind::ScrollDown => state.scroll_by(1),
+            MouseEventKind::ScrollUp => state.scroll_by(-1),
+            _ => {}
+        }
+    }
+
+    pub fn set_focused(&self, focused: bool) {
+        self.state.write_or_recover().focused = focused;
+    }
+
+    /// Act on the selected job, or on the selected artifact while the
+    /// artifacts are shown.
+    pub fn handle_action(&self, action: Action) {
+        let mut state = self.state.write_or_recover();
+        let page_size = state.page_size.max(1) as isize;
+
+        let view = match action {
+            Action::Artifacts => Some(DetailsView::Artifacts),
+            Action::TestResults => Some(DetailsView::TestResults),
+            _ => None,
+        };
+        if let Some(view) = view {
+            state.view = match state.view == view {
+                true => DetailsView::Jobs,
+                false => view,
+            };
+            state.test_scroll = 0;
+
+            if let Some(workflow) = state.workflow.clone() {
+                match state.view {
+                    DetailsView::Artifacts => {
+                        tokio::spawn(self.clone().fetch_artifacts(workflow));
+                    }
+                    DetailsView::TestResults => {
+                        tokio::spawn(self.clone().fetch_test_reports(workflow));
+                    }
+                    DetailsView::Jobs => {}
+                }
+            }
+            return;
+        }
+
+        if state.view == DetailsView::TestResults {
+            match action {
+                Action::ScrollDown => state.scroll_tests_by(1),
+                Action::ScrollUp => state.scroll_tests_by(-1),
+                Action::PageDown => state.scroll_tests_by(page_size),
+                Action::PageUp => state.scroll_tests_by(-page_size),
+                Action::First => state.scroll_tests_to(0),
+                Action::Last => state.scroll_tests_to(usize::MAX),
+                _ => {}
+            }
+            return;
+        }
+
+        if state.view == DetailsView::Artifacts {
+            match action {
+                Action::ScrollDown => state.scroll_artifacts_by(1),
+                Action::ScrollUp => state.scroll_artifacts_by(-1),
+                Action::PageDown => state.scroll_artifacts_by(page_size),
+                Action::PageUp => state.scroll_artifacts_by(-page_size),
+                Action::First => state.select_artifact(0),
+                Action::Last => state.select_artifact(usize::MAX),
+                Action::Download => {
+                    let (Some(workflow), Some(artifact)) =
+                        (state.workflow.clone(), state.selected_artifact().cloned())
+                    else {
+                        return;
+                    };
+
+                    if artifact.expired {
+                        state.notice = Some(format!("{} has expired", artifact.name));
+                        return;
+                    }
+                    // A download in progress isn't started again.
+                    if matches!(
+                        state.downloads.get(&artifact.id),
+                        Some(Download::InProgress { .. } | Download::Extracting)
+                    ) {
+                        return;
+                    }
+
+                    state.downloads.insert(
+                        artifact.id,
+                        Download::InProgress {
+                            received: 0,
+                            total: artifact.size_in_bytes,
+                        },
+                    );
+                    tokio::spawn(self.clone().download_artifact(workflow, artifact));
+                }
+                _ => {}
+            }
+            return;
+        }
+
+        match action {
+            Action::ScrollDown => state.scroll_by(1),
+            Action::ScrollUp => state.scroll_by(-1),
+            Action::PageDown => state.scroll_by(page_size),
+            Action::PageUp => state.scroll_by(-page_size),
+            Action::First => state.select(Some(0)),
+            Action::Last => state.select(Some(usize::MAX)),
+            // Starting the browser and copying can block, the state isn't
+            // locked meanwhile.
+            Action::Open => {
+                let Some(job) = state.selected_job().cloned() else {
+                    return;
+                };
+                drop(state);
+
+                if let Err(err) = self.browser.open(job.html_url.as_str()) {
+                    error!("Failed to open the job: {:?}", err);
+                    self.state.write_or_recover().notice =
+                        Some(format!("Unable to open a browser: {}", job.html_url));
+                }
+            }
+            Action::CopyUrl => {
+                let Some(job) = state.selected_job().cloned() else {
+                    return;
+                };
+                drop(state);
+
+                let notice = match clipboard::copy(job.html_url.as_str()) {
+                    Ok(()) => format!("Copied the URL of {}", job.name),
+                    Err(err) => err.to_string(),
+                };
+                self.state.write_or_recover().notice = Some(notice);
+            }
+            Action::RerunJob => {
+                let (Some(workflow), Some(job)) =
+                    (state.workflow.clone(), state.selected_job().cloned())
+                else {
+                    return;
+                };
+
+                state.notice = Some(format!("Re-running {}...", job.name));
+                tokio::spawn(self.clone().rerun_job(workflow, job));
+            }
+            _ => {}
+        }
+    }
+
+    async fn rerun_job(self, workflow: WorkflowRun, job: WorkflowJob) {
+        let notice = match self.github_service.rerun_job(&workflow, &job).await {
+            Ok(()) => format!("Re-run of {} requested", job.name),
+            Err(err) => {
+                error!("Failed to re-run job: {:?}", err);
+                err.to_string()
+            }
+        };
+
+        // The details may have moved on to another run.
+        let mut state = self.state.write_or_recover();
+        if state.workflow.as_ref().is_some_and(|w| w.id == workflow.id) {
+            state.notice = Some(notice);
+        }
+    }
+
+    /// Go back to the jobs, returns whether the artifacts or the test results
+    /// were shown.
+    pub fn show_jobs(&self) -> bool {
+        let mut state = self.state.write_or_recover();
+        std::mem::take(&mut state.view) != DetailsView::Jobs
+    }
+
+    /// The artifacts are fetched every time they are shown, a run can upload
+    /// more until it completes.
+    async fn fetch_artifacts(self, workflow: WorkflowRun) -> Vec<Artifact> {
+        let artifacts = self.github_service.list_artifacts(&workflow).await;
+
+        // The details may have moved on to another run.
+        let mut state = self.state.write_or_recover();
+        if state.workflow.as_ref().is_none_or(|w| w.id != workflow.id) {
+            return vec![];
+        }
+
+        match artifacts {
+            Ok(artifacts) => {
+                state.artifacts = Some(artifacts.clone());
+                let idx = state.artifact_table_state.selected().unwrap_or(0);
+                state.select_artifact(idx);
+                artifacts
+            }
+            Err(err) => {
+                error!("Failed to get the artifacts: {:?}", err);
+                state.artifacts.get_or_insert_default();
+                state.notice = Some(err.to_string());
+                vec![]
+            }
+        }
+    }
+
+    /// Download the small artifacts that look like test reports, a few at a
+    /// time, unless they were already downloaded. The reports are loaded once
+    /// they are extracted.
+    async fn fetch_test_reports(self, workflow: WorkflowRun) {
+        let artifacts = self.clone().fetch_artifacts(workflow.clone()).await;
+        let permits = Arc::new(Semaphore::new(Self::MAX_CONCURRENT_DOWNLOADS));
+        let mut set = JoinSet::new();
+
+        for artifact in artifacts {
+            if artifact.expired
+                || !junit::is_test_artifact(&artifact.name)
+                || artifact.size_in_bytes > Self::MAX_TEST_REPORT_SIZE
+            {
+                continue;
+            }
+
+            {
+                let mut state = self.state.write_or_recover();
+                if state
+                    .downloads
+                    .get(&artifact.id)
+                    .is_some_and(|d| !matches!(d, Download::Failed(_)))
+                {
+                    continue;
+                }
+                state.downloads.insert(
+                    artifact.id,
+                    Download::InProgress {
+                        received: 0,
+                        total: artifact.size_in_bytes,
+                    },
+                );
+            }
+
+            let (this, workflow, permits) = (self.clone(), workflow.clone(), permits.clone());
+            set.spawn(async move {
+                let _permit = permits.acquire_owned().await;
+                this.download_artifact(workflow, artifact).await;
+            });
+        }
+
+        while let Some(res) = set.join_next().await {
+            if let Err(err) = res {
+                error!("Failed to download the test reports: {:?}", err);
+            }
+        }
+    }
+
+    /// Download the archive of an artifact next to its destination, then
+    /// extract it.
+    async fn download_artifact(self, workflow: WorkflowRun, artifact: Artifact) {
+        let download = match self.download_and_extract(&workflow, &artifact).await {
+            Ok(directory) => Download::Done(directory),
+            Err(err) => {
+                error!("Failed to download the artifact: {:?}", err);
+                Download::Failed(err)
+            }
+        };
+
+        if let Download::Done(directory) = &download {
+            self.load_test_report(&artifact, directory.clone()).await;
+        }
+
+        self.state
+            .write_or_recover()
+            .downloads
+            .insert(artifact.id, download);
+    }
+
+    /// Look for JUnit reports in an extracted artifact.
+    async fn load_test_report(&self, artifact: &Artifact, directory: PathBuf) {
+        match tokio::task::spawn_blocking(move || junit::load(&directory)).await {
+            Ok(Ok(Some(report))) => {
+                self.state
+                    .write_or_recover()
+                    .test_reports
+                    .insert(artifact.id, report);
+            }
+            Ok(Ok(None)) => {}
+            Ok(Err(err)) => error!("Failed to load the test reports: {:?}", err),
+            Err(err) => error!("Failed to load the test reports: {:?}", err),
+        }
+    }
+
+    async fn download_and_extract(
+        &self,
+        workflow: &WorkflowRun,
+        artifact: &Artifact,
+    ) -> Result<PathBuf, String> {
+        let Some(directory) = artifacts::destination(&self.artifact_settings, workflow, artifact)
+        else {
+            return Err("Unable to find the download directory".to_string());
+        };
+        let archive = directory.with_extension("zip");
+        if let Some(parent) = archive.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|e| format!("Unable to create {}: {}", parent.display(), e))?;
+        }
+
+        let (progress, mut received) = watch::channel(0);
+        let download = self
+            .github_service
+            .download_artifact(workflow, artifact, &archive, &progress);
+        tokio::pin!(download);
+
+        let result = loop {
+            tokio::select! {
+                result = &mut download => break result,
+                Ok(()) = received.changed() => {
+                    let received = *received.borrow_and_update();
+                    self.state.write_or_recover().downloads.insert(
+                        artifact.id,
+                        Download::InProgress { received, total: artifact.size_in_bytes },
+                    );
+                }
+            }
+        };
+        result.map_err(|e| e.to_string())?;
+
+        self.state
+            .write_or_recover()
+            .downloads
+            .insert(artifact.id, Download::Extracting);
+
+        let extracted = {
+            let (archive, directory) = (archive.clone(), directory.clone());
+            tokio::task::spawn_blocking(move || artifacts::extract(&archive, &directory)).await
+        };
+        let _ = tokio::fs::remove_file(&archive).await;
+
+        match extracted {
+            Ok(Ok(())) => Ok(directory),
+            Ok(Err(err)) => Err(err.to_string()),
+            Err(err) => Err(err.to_string()),
+        }
+    }
+
     pub fn hide(&mut self) {
-        let mut state = self.state.write().unwrap();
+        self.stop();
+
+        let mut state = self.state.write_or_recover();
+        state.workflow = None;
+        state.notice = None;
         state.workflow_jobs.clear();
+        state.earlier_attempts.clear();
+        state.check_runs.clear();
+        state.stats = None;
+        state.selected_job = None;
+        state.table_state = TableState::default();
+        state.view = DetailsView::Jobs;
+        state.artifacts = None;
+        state.artifact_table_state = TableState::default();
 
         self.visible = false;
     }
 
-    async fn sync_data(self, workflow: WorkflowRun) {
-        let period = Duration::from_secs(60);
-        let mut interval = time::interval(period);
+    /// The jobs are fetched first, the pull request, the earlier attempts
+    /// and the statistics are fetched alongside them.
+    async fn sync_data(self, workflow: WorkflowRun, fetch_stats: bool) {
+        self.load_cached_jobs(&workflow).await;
+
+        let extras = async {
+            tokio::join!(
+                self.fetch_pull_request_title(&workflow),
+                self.fetch_job_attempts(&workflow),
+                self.fetch_workflow_stats(&workflow, fetch_stats),
+            )
+        };
+        tokio::join!(self.refresh_jobs(&workflow), extras);
+    }
 
+    /// Refresh the jobs until the task is aborted, when the details are hidden
+    /// or another run is shown.
+    async fn refresh_jobs(&self, workflow: &WorkflowRun) {
+        let mut checked_jobs = 0;
         loop {
-            interval.tick().await;
-            if !self.visible {
-                return;
+            self.fetch_workflow_jobs(workflow).await;
+
+            // The jobs report their annotations when they complete.
+            let completed_jobs = self.completed_jobs();
+            // A failed request is retried on the next refresh.
+            if completed_jobs > checked_jobs && self.fetch_check_runs(workflow).await {
+                checked_jobs = completed_jobs;
             }
-            self.fetch_workflow_jobs(&workflow).await;
+
+            time::sleep(self.refresh_interval()).await;
         }
     }
 
+    fn completed_jobs(&self) -> usize {
+        self.state
+            .read_or_recover()
+            .workflow_jobs
+            .iter()
+            .filter(|j| j.completed_at.is_some())
+            .count()
+    }
+
+    /// Fetch the check runs of the jobs, returns whether they were fetched.
+    async fn fetch_check_runs(&self, workflow: &WorkflowRun) -> bool {
+        match self.github_service.list_check_runs(workflow).await {
+            Ok(check_runs) => {
+                self.update(|state| state.check_runs = check_runs);
+                true
+            }
+            Err(err) => {
+                error!("Failed to get the check runs: {:?}", err);
+                false
+            }
+        }
+    }
+
+    /// Refresh quickly while some jobs are still running.
+    fn refresh_interval(&self) -> Duration {
+        let running = self
+            .state
+            .read_or_recover()
+            .workflow_jobs
+            .iter()
+            .any(|j| j.completed_at.is_none());
+
+        self.refresh_policy.interval(None, running)
+    }
+
     async fn fetch_workflow_jobs(&self, workflow: &WorkflowRun) {
         self.set_loading_state(LoadingState::Loading);
 
         let jobs = self.github_service.list_jobs(workflow).await;
 
         match jobs {
-            Ok(j) => self.on_load(j),
+            Ok(j) => self.on_load(j).await,
             Err(err) => self.on_err(&err),
         }
     }
 
-    fn on_load(&self, jobs: Vec<WorkflowJob>) {
-        let mut state = self.state.write().unwrap();
+    /// Show the last known jobs of the run while they are being fetched.
+    async fn load_cached_jobs(&self, workflow: &WorkflowRun) {
+        let Some(history) = &self.history else {
+            return;
+        };
+
+        let run_id = workflow.id;
+        match history.run_blocking(move |h| h.jobs(run_id)).await {
+            Ok(jobs) => self.update(|state| state.set_attempts(jobs)),
+            Err(err) => error!("Failed to load cached jobs: {}", err),
+        }
+    }
+
+    /// The earlier attempts don't change, they are only fetched once.
+    async fn fetch_job_attempts(&self, workflow: &WorkflowRun) {
+        if workflow.run_attempt <= 1 {
+            return;
+        }
+
+        match self.github_service.list_job_attempts(workflow).await {
+            Ok(jobs) => {
+                self.update(|state| state.set_earlier_attempts(jobs.clone(), workflow.run_attempt));
+                self.save_jobs(jobs).await;
+            }
+            Err(err) => error!("Failed to get the attempts of the run: {:?}", err),
+        }
+    }
+
+    /// The run only has the title of its pull request for the pull request
+    /// events, e.g. not for the pushes to the branch of the pull request.
+    async fn fetch_pull_request_title(&self, workflow: &WorkflowRun) {
+        let Some(pr) = workflow
+            .pull_request
+            .as_ref()
+            .filter(|pr| pr.title.is_none())
+        else {
+            return;
+        };
+
+        match self
+            .github_service
+            .get_pull_request_title(workflow, pr.number)
+            .await
+        {
+            Ok(title) => self.update(|state| {
+                if let Some(pr) = state
+                    .workflow
+                    .as_mut()
+                    .and_then(|w| w.pull_request.as_mut())
+                {
+                    pr.title = Some(title);
+                }
+            }),
+            Err(err) => error!("Failed to get the pull request: {:?}", err),
+        }
+    }
+
+    /// Compute the statistics of the workflow from its last completed runs,
+    /// unless they were computed recently or they aren't to be fetched.
+    ///
+    /// The queue times are taken from the jobs of those runs, which are fetched
+    /// concurrently. The runs from the history store complete the ones returned
+    /// by the API.
+    async fn fetch_workflow_stats(&self, workflow: &WorkflowRun, fetch_stats: bool) {
+        if self.load_cached_stats(workflow) || !fetch_stats {
+            return;
+        }
+
+        let mut runs = match self
+            .github_service
+            .list_workflow_history(workflow, self.stats_runs)
+            .await
+        {
+            Ok(runs) => runs,
+            Err(err) => {
+                error!("Failed to get workflow history: {:?}", err);
+                vec![]
+            }
+        };
+
+        if let Some(history) = &self.history {
+            let fetched = runs.clone();
+            self.write_history(move |h| h.save_runs(&fetched)).await;
+
+            let (workflow, count) = (workflow.clone(), u32::from(self.stats_runs));
+            match history
+                .run_blocking(move |h| h.workflow_history(&workflow, count))
+                .await
+            {
+                Ok(stored) => {
+                    let known: HashSet<_> = runs.iter().map(|r| r.id).collect();
+                    runs.extend(stored.into_iter().filter(|r| !known.contains(&r.id)));
+                    runs.sort_by(|a, b| Ord::cmp(&a.start_time, &b.start_time).reverse());
+                    runs.truncate(usize::from(self.stats_runs));
+                }
+                Err(err) => error!("Failed to load workflow history: {}", err),
+            }
+        }
+
+        let mut set = JoinSet::new();
+
+        runs.iter().for_each(|run| {
+            let github_service = self.github_service.clone();
+            let run = run.clone();
+            // The attempts of the re-run runs feed the flakiness detection.
+            set.spawn(async move {
+                match run.run_attempt {
+                    1 => github_service.list_jobs(&run).await,
+                    _ => github_service.list_job_attempts(&run).await,
+                }
+            });
+        });
+
+        let mut jobs = vec![];
+
+        while let Some(res) = set.join_next().await {
+            match res {
+                Ok(Ok(j)) => jobs.extend(j),
+                Ok(Err(err)) => error!("Failed to get workflow jobs: {:?}", err),
+                Err(err) => error!("Failed to get workflow jobs: {:?}", err),
+            }
+        }
+
+        let stats = WorkflowStats::compute(&runs, &jobs);
+        self.save_jobs(jobs).await;
+
+        self.state
+            .write_or_recover()
+            .stats_cache
+            .insert(workflow.workflow_id, (time::Instant::now(), stats.clone()));
+        self.update(|state| state.stats = Some(stats));
+    }
+
+    /// Show the statistics of the workflow computed recently, returns whether
+    /// there were some.
+    fn load_cached_stats(&self, workflow: &WorkflowRun) -> bool {
+        let mut state = self.state.write_or_recover();
+        state
+            .stats_cache
+            .retain(|_, (computed_at, _)| computed_at.elapsed() < Self::STATS_TTL);
+
+        let Some((_, stats)) = state.stats_cache.get(&workflow.workflow_id) else {
+            return false;
+        };
+        if state.generation == self.generation {
+            state.stats = Some(stats.clone());
+        }
+
+        true
+    }
+
+    async fn save_jobs(&self, jobs: Vec<WorkflowJob>) {
+        self.write_history(move |h| h.save_jobs(&jobs)).await;
+    }
+
+    /// Write to the history off the async workers, the SQLite calls block.
+    async fn write_history(
+        &self,
+        write: impl FnOnce(&HistoryStore) -> exn::Result<(), StorageError> + Send + 'static,
+    ) {
+        let Some(history) = &self.history else {
+            return;
+        };
+
+        if let Err(err) = history.run_blocking(write).await {
+            error!("Failed to update the history: {:?}", err);
+        }
+    }
 
-        state.workflow_jobs = jobs;
+    async fn on_load(&self, jobs: Vec<WorkflowJob>) {
+        self.update(|state| {
+            state.set_jobs(jobs.clone());
+            state.loading_state = LoadingState::Loaded(chrono::Local::now());
+        });
 
-        state.loading_state = LoadingState::Loaded(chrono::Local::now());
+        self.save_jobs(jobs).await;
     }
 
     fn on_err(&self, err: &Exn<ServiceError>) {
         self.set_loading_state(LoadingState::Error(err.to_string()));
     }
 
-    fn set_loading_state(&self, state: LoadingState) {
-        self.state.write().unwrap().loading_state = state;
+    fn set_loading_state(&self, loading_state: LoadingState) {
+        self.update(|state| state.loading_state = loading_state);
+    }
+
+    /// The artifacts of the run, with the progress of their downloads.
+    fn render_artifacts(&self, state: &mut WorkflowDetailsState, area: Rect, buf: &mut Buffer) {
+        let mut block = Block::bordered()
+            .title("Artifacts")
+            .title_bottom(self.artifacts_footer.as_str());
+        if let Some(notice) = state.notice.clone() {
+            block = block.title_bottom(Line::from(notice).right_aligned());
+        }
+        if state.focused {
+            block = block.border_type(BorderType::Thick);
+        }
+
+        let artifacts = match &state.artifacts {
+            None => {
+                Paragraph::new("Loading...").block(block).render(area, buf);
+                return;
+            }
+            Some(artifacts) if artifacts.is_empty() => {
+                Paragraph::new("The run has no artifacts")
+                    .block(block)
+                    .render(area, buf);
+                return;
+            }
+            Some(artifacts) => artifacts,
+        };
+
+        let rows: Vec<_> = artifacts
+            .iter()
+            .map(|a| {
+                let expires = match a.expires_at {
+                    _ if a.expired => Cell::from("expired").style(self.theme.skipped_style()),
+                    Some(t) => Cell::from(self.time_formatter.time(t)),
+                    None => Cell::from("-"),
+                };
+
+                Row::new(vec![
+                    Cell::from(a.name.clone()),
+                    Cell::from(format_size(a.size_in_bytes)),
+                    expires,
+                    self.download_cell(state.downloads.get(&a.id)),
+                ])
+            })
+            .collect();
+
+        let header = Row::new(vec!["Name", "Size", "Expires", "Download"]).style(self.theme.header);
+        let widths = [
+            Constraint::Max(64),
+            Constraint::Length(12),
+            Constraint::Max(32),
+            Constraint::Fill(1),
+        ];
+
+        let table = Table::new(rows, widths)
+            .header(header)
+            .block(block)
+            .highlight_spacing(HighlightSpacing::Always)
+            .highlight_symbol(">>")
+            .row_highlight_style(self.theme.highlight);
+
+        // The header and the borders take 4 lines.
+        state.page_size = usize::from(area.height.saturating_sub(4));
+        StatefulWidget::render(table, area, buf, &mut state.artifact_table_state);
+    }
+
+    /// The counts of the tests, then the tests that failed with their message.
+    fn render_test_results(&self, state: &mut WorkflowDetailsState, area: Rect, buf: &mut Buffer) {
+        let mut block = Block::bordered().title("Test Results");
+        if let Some(notice) = state.notice.clone() {
+            block = block.title_bottom(Line::from(notice).right_aligned());
+        }
+        if state.focused {
+            block = block.border_type(BorderType::Thick);
+        }
+
+        let report = state.test_report();
+        let downloading = state.downloading_test_reports();
+        state.page_size = usize::from(area.height.saturating_sub(2));
+
+        let large = state.large_test_artifacts();
+        if large > 0 {
+            block = block.title_bottom(format!(
+                "{} large artifacts not downloaded, {}",
+                large, self.large_artifacts_hint
+            ));
+        }
+
+        let Some(report) = report else {
+            let text = match (&state.artifacts, downloading) {
+                (None, _) | (_, true) => "Downloading the test reports...",
+                _ => "No JUnit report found in the artifacts",
+            };
+            Paragraph::new(text).block(block).render(area, buf);
+            return;
+        };
+
+        let mut counts = format!(
+            "{} tests: {} passed, {} failed, {} skipped",
+            report.total(),
+            report.passed,
+            report.failed,
+            report.skipped
+        );
+        if downloading {
+            counts.push_str(" (downloading more reports...)");
+        }
+
+        let mut lines = vec![Line::from(Span::styled(
+            counts,
+            Style::new().add_modifier(Modifier::BOLD),
+        ))];
+        for failure in &report.failures {
+            lines.push(Line::from(Span::styled(
+                format!("{} {}", self.theme.symbols.failure, failure.name),
+                self.theme.failure_style(),
+            )));
+            if !failure.message.is_empty() {
+                lines.push(Line::from(format!("    {}", failure.message)));
+            }
+        }
+
+        // The results may have shrunk, or the area grown, since the last scroll.
+        state.test_lines = lines.len();
+        state.scroll_tests_to(usize::from(state.test_scroll));
+
+        Paragraph::new(lines)
+            .block(block)
+            .scroll((state.test_scroll, 0))
+            .render(area, buf);
+    }
+
+    fn download_cell<'a>(&self, download: Option<&Download>) -> Cell<'a> {
+        match download {
+            None => Cell::from(""),
+            Some(Download::InProgress { received, total }) => {
+                let percent = (*received * 100).checked_div(*total).unwrap_or(0).min(100);
+                Cell::from(format!(
+                    "{}% ({} of {})",
+                    percent,
+                    format_size(*received),
+                    format_size(*total)
+                ))
+                .style(self.theme.running_style())
+            }
+            Some(Download::Extracting) => {
+                Cell::from("Extracting...").style(self.theme.running_style())
+            }
+            Some(Download::Done(directory)) => Cell::from(format!(
+                "{} {}",
+                self.theme.symbols.success,
+                directory.display()
+            ))
+            .style(self.theme.success_style()),
+            Some(Download::Failed(err)) => {
+                Cell::from(format!("{} {}", self.theme.symbols.failure, err))
+                    .style(self.theme.failure_style())
+            }
+        }
+    }
+
+    /// Update the state, unless the details moved on to another run since
+    /// this task was started.
+    fn update(&self, f: impl FnOnce(&mut WorkflowDetailsState)) {
+        let mut state = self.state.write_or_recover();
+
+        if state.generation == self.generation {
+            f(&mut state);
+        }
     }
 }
 
 impl Widget for &WorkflowDetailsWidget {
     fn render(self, area: Rect, buf: &mut Buffer) {
-        let mut state = self.state.write().unwrap();
+        let mut state = self.state.write_or_recover();
+
+        // The last few attempts, only for the re-run runs.
+        let attempts_height = match state.earlier_attempts.len() {
+            0 => 0,
+            n => n.min(WorkflowDetailsWidget::MAX_ATTEMPTS) as u16 + 2,
+        };
+        let layout = Layout::vertical([
+            Constraint::Length(5),
+            Constraint::Length(attempts_height),
+            Constraint::Length(5),
+            Constraint::Fill(1),
+        ]);
+        let [header_area, attempts_area, stats_area, jobs_area] = area.layout(&layout);
+
+        render_header(state.workflow.as_ref(), header_area, buf);
+        if attempts_height > 0 {
+            render_attempts(&state.earlier_attempts, &self.theme, attempts_area, buf);
+        }
+        let placeholder = match state.stats_deferred {
+            true => self.stats_hint.as_str(),
+            false => "Loading...",
+        };
+        render_stats(
+            state.stats.as_ref(),
+            self.stats_runs,
+            placeholder,
+            stats_area,
+            buf,
+        );
+
+        // The annotations of the selected job, below the jobs.
+        let check_lines = state
+            .check_run()
+            .map(|c| check_run_lines(c, &self.theme))
+            .unwrap_or_default();
+        let check_height = match check_lines.len() {
+            0 => 0,
+            n => n.min(WorkflowDetailsWidget::MAX_CHECK_LINES) as u16 + 2,
+        };
+        let [jobs_area, check_area] = jobs_area.layout(&Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Length(check_height),
+        ]));
+        if check_height > 0 {
+            let title = state
+                .check_run()
+                .map_or(String::new(), |c| format!("Annotations of {}", c.name));
+            Paragraph::new(check_lines)
+                .block(Block::bordered().title(title))
+                .render(check_area, buf);
+        }
+
+        match state.view {
+            DetailsView::Jobs => {}
+            DetailsView::Artifacts => {
+                self.render_artifacts(&mut state, jobs_area, buf);
+                return;
+            }
+            DetailsView::TestResults => {
+                self.render_test_results(&mut state, jobs_area, buf);
+                return;
+            }
+        }
 
-        let loading_state = Line::from(format!("{}", state.loading_state)).right_aligned();
-        let block = Block::bordered()
-            .title("Workflow Jobs")
+        let loading_state = Line::from(self.time_formatter.loading_state(&state.loading_state))
+            .style(self.theme.loading_state(&state.loading_state))
+            .right_aligned();
+        let title = match state.jobs_attempt() {
+            Some(attempt) => format!("Workflow Jobs (attempt {})", attempt),
+            None => "Workflow Jobs".to_string(),
+        };
+        let mut block = Block::bordered()
+            .title(title)
             .title(loading_state)
-            .title_bottom("esc to close");
+            .title_bottom(self.footer.as_str());
+        if let Some(notice) = state.notice.clone() {
+            block = block.title_bottom(Line::from(notice).right_aligned());
+        }
+        if state.focused {
+            block = block.border_type(BorderType::Thick);
+        }
 
         let widths = [
             Constraint::Max(120),   // Job Name
             Constraint::Max(32),    // Started At
             Constraint::Max(32),    // Completed At
+            Constraint::Length(12), // Queued
+            Constraint::Length(12), // Duration
             Constraint::Length(16), // Status
             Constraint::Length(16), // Completion
         ];
@@ -129,41 +1316,256 @@ impl Widget for &WorkflowDetailsWidget {
             "Job Name",
             "Started At",
             "Completed At",
+            "Queued",
+            "Duration",
             "Status",
             "Conclusion",
         ])
-        .style(Style::new().bold());
+        .style(self.theme.header);
 
-        let rows = state.workflow_jobs.iter();
+        let rows = state
+            .workflow_jobs
+            .iter()
+            .map(|j| job_row(j, &self.time_formatter, &self.theme));
 
         let table = Table::new(rows, widths)
             .header(header)
             .block(block)
             .highlight_spacing(HighlightSpacing::Always)
             .highlight_symbol(">>")
-            .row_highlight_style(Style::new().on_blue());
+            .row_highlight_style(self.theme.highlight);
 
-        StatefulWidget::render(table, area, buf, &mut state.table_state);
+        state.table_area = jobs_area;
+        // The header and the borders take 4 lines.
+        state.page_size =
+            usize::from(jobs_area.height.saturating_sub(4) / WorkflowDetailsWidget::ROW_HEIGHT);
+        StatefulWidget::render(table, jobs_area, buf, &mut state.table_state);
     }
 }
 
-impl From<&WorkflowJob> for Row<'_> {
-    fn from(r: &WorkflowJob) -> Self {
-        let j = r.clone();
-        Row::new(vec![
-            j.name,
-            j.started_at
-                .with_timezone(&chrono::Local)
-                .format("%Y-%m-%d %H:%M:%S")
-                .to_string(),
-            j.completed_at.map_or("".to_string(), |t| {
-                t.with_timezone(&chrono::Local)
-                    .format("%Y-%m-%d %H:%M:%S")
-                    .to_string()
-            }),
-            j.status.to_string(),
-            j.conclusion.to_string(),
+/// What triggered the run, on which commit, and by whom.
+fn render_header(workflow: Option<&WorkflowRun>, area: Rect, buf: &mut Buffer) {
+    let Some(w) = workflow else {
+        Block::bordered().render(area, buf);
+        return;
+    };
+
+    let block = Block::bordered().title(format!("{} #{}", w.name, w.run_number));
+    let bold = Style::new().add_modifier(Modifier::BOLD);
+
+    let trigger = match &w.pull_request {
+        Some(pr) => Line::from(vec![
+            Span::styled(format!("Pull request #{}", pr.number), bold),
+            Span::raw(format!(": {}", pr.title.as_deref().unwrap_or("..."))),
+        ]),
+        None => Line::from(vec![
+            Span::styled(w.event.clone(), bold),
+            Span::raw(format!(" on {}", w.branch)),
+        ]),
+    };
+
+    let commit_title = w.commit_message.lines().next().unwrap_or_default();
+    let commit = Line::from(vec![
+        Span::styled(w.short_sha().to_string(), bold),
+        Span::raw(format!(" {} ({})", commit_title, w.commit_author)),
+    ]);
+
+    let mut run = format!("Attempt {} by {}", w.run_attempt, w.triggering_actor);
+    if w.triggering_actor != w.actor {
+        run.push_str(&format!(", first run by {}", w.actor));
+    }
+
+    Paragraph::new(vec![trigger, commit, Line::from(run)])
+        .block(block)
+        .render(area, buf);
+}
+
+/// The outcome of the last attempts before the latest one, with the jobs that
+/// failed.
+fn render_attempts(
+    attempts: &[(u64, Vec<WorkflowJob>)],
+    theme: &Theme,
+    area: Rect,
+    buf: &mut Buffer,
+) {
+    let block = Block::bordered().title("Earlier Attempts");
+
+    let skip = attempts
+        .len()
+        .saturating_sub(WorkflowDetailsWidget::MAX_ATTEMPTS);
+    let lines: Vec<_> = attempts[skip..]
+        .iter()
+        .map(|(attempt, jobs)| {
+            let failed: Vec<_> = jobs
+                .iter()
+                .filter(|j| {
+                    matches!(
+                        j.conclusion,
+                        WorkflowJobConclusion::Failure | WorkflowJobConclusion::TimedOut
+                    )
+                })
+                .map(|j| j.name.as_str())
+                .collect();
+
+            let outcome = match failed.is_empty() {
+                true => format!("{} {} jobs", theme.symbols.success, jobs.len()),
+                false => format!(
+                    "{} {} of {} jobs failed: {}",
+                    theme.symbols.failure,
+                    failed.len(),
+                    jobs.len(),
+                    failed.join(", ")
+                ),
+            };
+
+            Line::from(format!("Attempt {}  {}", attempt, outcome))
+        })
+        .collect();
+
+    Paragraph::new(lines).block(block).render(area, buf);
+}
+
+/// The title and the summary of a check run, then its annotations, the
+/// failures first.
+fn check_run_lines<'a>(check_run: &CheckRun, theme: &Theme) -> Vec<Line<'a>> {
+    let mut lines = vec![];
+
+    if let Some(title) = &check_run.title {
+        lines.push(Line::from(Span::styled(
+            title.clone(),
+            Style::new().add_modifier(Modifier::BOLD),
+        )));
+    }
+    if let Some(summary) = &check_run.summary {
+        lines.extend(
+            summary
+                .lines()
+                .filter(|l| !l.trim().is_empty())
+                .take(3)
+                .map(|l| Line::from(l.to_string())),
+        );
+    }
+
+    let mut annotations: Vec<_> = check_run.annotations.iter().collect();
+    annotations.sort_by_key(|a| a.level);
+
+    lines.extend(annotations.into_iter().map(|a| {
+        let (symbol, style) = theme.annotation(a.level);
+        let location = match a.start_line == a.end_line {
+            true => format!("{}:{}", a.path, a.start_line),
+            false => format!("{}:{}-{}", a.path, a.start_line, a.end_line),
+        };
+        let message = a.message.lines().next().unwrap_or_default();
+        let message = match &a.title {
+            Some(title) => format!("{}: {}", title, message),
+            None => message.to_string(),
+        };
+
+        Line::from(vec![
+            Span::styled(format!("{} {} ", symbol, location), style),
+            Span::raw(message),
         ])
-        .height(2)
+    }));
+
+    lines
+}
+
+/// The statistics of the workflow, `placeholder` until they are computed.
+fn render_stats(
+    stats: Option<&WorkflowStats>,
+    stats_runs: u8,
+    placeholder: &str,
+    area: Rect,
+    buf: &mut Buffer,
+) {
+    let block = Block::bordered().title(format!("Workflow Statistics (last {} runs)", stats_runs));
+
+    let Some(stats) = stats else {
+        Paragraph::new(placeholder).block(block).render(area, buf);
+        return;
+    };
+
+    let inner = block.inner(area);
+    block.render(area, buf);
+
+    let layout = Layout::horizontal([Constraint::Length(48), Constraint::Fill(1)]);
+    let [text_area, sparkline_area] = inner.layout(&layout);
+
+    let fmt = |d: Option<chrono::TimeDelta>| d.map_or("-".to_string(), format_duration);
+    let success_rate = stats
+        .success_rate
+        .map_or("-".to_string(), |r| format!("{:.0}%", r * 100.0));
+
+    let text = vec![
+        Line::from(format!(
+            "Duration   p50: {:<10} p95: {}",
+            fmt(stats.p50_duration),
+            fmt(stats.p95_duration)
+        )),
+        Line::from(format!(
+            "Queue time p50: {:<10} p95: {}",
+            fmt(stats.p50_queue_time),
+            fmt(stats.p95_queue_time)
+        )),
+        Line::from(format!("Success    {} ({} runs)", success_rate, stats.runs)),
+    ];
+
+    Paragraph::new(text).render(text_area, buf);
+    Sparkline::default()
+        .data(&stats.durations)
+        .render(sparkline_area, buf);
+}
+
+fn job_row<'a>(j: &WorkflowJob, time_formatter: &TimeFormatter, theme: &Theme) -> Row<'a> {
+    let queue_time = format_duration(j.queue_time());
+    let duration =
+        time_formatter.duration(j.duration(chrono::Utc::now()), j.completed_at.is_none());
+    let status = theme.job_status(&j.status);
+    let conclusion = theme.job_conclusion(&j.conclusion);
+    let j = j.clone();
+    Row::new(vec![
+        Cell::from(j.name),
+        Cell::from(time_formatter.time(j.started_at)),
+        Cell::from(
+            j.completed_at
+                .map_or("".to_string(), |t| time_formatter.time(t)),
+        ),
+        Cell::from(queue_time),
+        Cell::from(duration),
+        status,
+        conclusion,
+    ])
+    .height(WorkflowDetailsWidget::ROW_HEIGHT)
+}
+
+#[cfg(test)]
+mod tests {
+    use fake::{Fake, Faker};
+
+    use super::*;
+    use crate::service::workflows::MockGitHubService;
+
+    #[test]
+    fn updates_of_a_previous_generation_are_discarded() {
+        let mut details = WorkflowDetailsWidget {
+            github_service: Arc::new(MockGitHubService::new()),
+            ..Default::default()
+        };
+        // The background task works on a clone of the widget.
+        let task = details.clone();
+        let jobs = || vec![Faker.fake::<WorkflowJob>()];
+
+        task.update(|state| state.workflow_jobs = jobs());
+        assert_eq!(details.state.read_or_recover().workflow_jobs.len(), 1);
+
+        // Hiding the details moves on to the next generation.
+        details.hide();
+        task.update(|state| state.workflow_jobs = jobs());
+        assert!(details.state.read_or_recover().workflow_jobs.is_empty());
+
+        let mut task = details.clone();
+        task.generation = details.state.read_or_recover().generation;
+        task.update(|state| state.workflow_jobs = jobs());
+        assert_eq!(details.state.read_or_recover().workflow_jobs.len(), 1);
     }
 }
@@ -1,31 +1,52 @@
+use std::collections::{HashMap, HashSet};
 use std::ops::Deref;
 use std::sync::{Arc, RwLock};
 use std::time::Duration;
 
-use crossterm::event::{Event, KeyCode};
-use exn::Exn;
+use crossterm::event::{Event, MouseButton, MouseEvent, MouseEventKind};
+use exn::{Exn, Result};
+use log::error;
+use octocrab::models::RunId;
 use ratatui::buffer::Buffer;
-use ratatui::layout::{Constraint, Rect};
-use ratatui::style::Style;
+use ratatui::layout::{Constraint, Layout, Position, Rect};
 use ratatui::text::Line;
 use ratatui::widgets::{
     Block,
+    BorderType,
     Clear,
     HighlightSpacing,
+    Paragraph,
     Row,
     StatefulWidget,
     Table,
     TableState,
     Widget,
 };
-use tokio::sync::mpsc;
+use tokio::sync::{Mutex, mpsc};
+use tokio::task::JoinSet;
 use tokio::time;
 
+use crate::backoff::Backoff;
+use crate::browser::Browser;
+use crate::configuration::{DetailsPlacement, LayoutMode, Settings};
 use crate::error::ServiceError;
-use crate::models::{Repository, WorkflowRun};
+use crate::flaky::FlakyJob;
+use crate::history::HistoryStore;
+use crate::keymap::{Action, Input, Keymap};
+use crate::models::{Repository, RepositorySource, RunnerScope, WorkflowRun};
 use crate::service::workflows::{GitHubService, Service};
-use crate::widgets::state::LoadingState;
+use crate::supervisor::supervise;
+use crate::widgets::format::{TimeFormatter, format_duration};
+use crate::widgets::layout;
+use crate::widgets::layout::Columns;
+use crate::widgets::mouse::{DoubleClick, row_at};
+use crate::widgets::refresh::RefreshPolicy;
+use crate::widgets::runners::{RunnersView, RunnersWidget};
+use crate::widgets::state::{LoadingState, RwLockExt};
+use crate::widgets::theme::Theme;
+use crate::widgets::usage::UsageWidget;
 use crate::widgets::workflow_details::WorkflowDetailsWidget;
+use crate::{clipboard, flaky};
 
 /// A widget that displays a list of workflow runs.
 ///
@@ -37,41 +58,284 @@ use crate::widgets::workflow_details::WorkflowDetailsWidget;
 #[derive(Debug, Clone)]
 pub struct WorkflowRunListWidget {
     github_service: Arc<dyn GitHubService>,
+    sources: Vec<RepositorySource>,
     repos: Vec<Repository>,
+    discovered: bool,
+    discovery_retry_at: Option<time::Instant>,
+    discovery_backoff: Backoff,
+    refresh_policy: RefreshPolicy,
+    repo_refreshes: HashMap<RepoKey, RepoRefresh>,
+    history: Option<Arc<HistoryStore>>,
+    /// The snapshots to save in the history, in the background.
+    snapshots: Option<mpsc::UnboundedSender<Vec<WorkflowRun>>>,
+    time_formatter: TimeFormatter,
+    theme: Theme,
+    layout_mode: LayoutMode,
+    details_placement: DetailsPlacement,
+    /// The run whose details are shown.
+    details_run: Option<RunId>,
+    /// Whether the keys go to the details next to the runs.
+    details_focused: bool,
+    /// The run whose details will be shown once the selection settles.
+    pending_details: Option<(time::Instant, RunId)>,
+    /// The hints at the bottom of the table.
+    footer: String,
+    /// The hint at the bottom of the popups.
+    close_hint: String,
+    browser: Browser,
+    double_click: DoubleClick,
     state: Arc<RwLock<WorkflowListState>>,
     details_widget: Arc<RwLock<WorkflowDetailsWidget>>,
+    runners_widget: RunnersWidget,
+    usage_widget: UsageWidget,
 }
 
 #[derive(Debug, Default)]
 struct WorkflowListState {
     workflow_runs: Vec<WorkflowRun>,
+    /// The repositories whose runs are being fetched.
+    loading_repos: HashSet<RepoKey>,
     loading_state: LoadingState,
+    refreshed_at: Option<chrono::DateTime<chrono::Local>>,
+    next_refresh_at: Option<chrono::DateTime<chrono::Local>>,
+    /// The selected run, the index in `table_state` is derived from it after
+    /// every reload.
+    selected_run: Option<RunId>,
     table_state: TableState,
+    page_size: usize,
+    /// The areas of the table and of the details popup, as of the last render.
+    table_area: Rect,
+    details_area: Option<Rect>,
+    /// The height of the rows and of the header, which depend on the layout.
+    row_height: u16,
+    header_height: u16,
+    status: Option<StatusMessage>,
+    flaky_jobs: Option<FlakyJobs>,
 }
 
+/// The flakiest jobs of a repository, shown over the runs.
+#[derive(Debug, Clone)]
+struct FlakyJobs {
+    repo: String,
+    /// The runs of the repository whose jobs were saved in the history, the
+    /// ranking is only based on them.
+    observed_runs: usize,
+    jobs: Vec<FlakyJob>,
+}
+
+/// A message shown at the bottom of the runs for a while, e.g. the URL that
+/// couldn't be opened.
+#[derive(Debug, Clone)]
+struct StatusMessage {
+    text: String,
+    is_error: bool,
+    expires_at: time::Instant,
+}
+
+impl WorkflowListState {
+    fn select(&mut self, idx: Option<usize>) {
+        let idx = idx.map(|i| i.min(self.workflow_runs.len().saturating_sub(1)));
+        let run = idx.and_then(|i| self.workflow_runs.get(i));
+
+        self.selected_run = run.map(|r| r.id);
+        self.table_state.select(idx.filter(|_| run.is_some()));
+    }
+
+    fn selected_run(&self) -> Option<&WorkflowRun> {
+        let id = self.selected_run?;
+        self.workflow_runs.iter().find(|r| r.id == id)
+    }
+
+    /// Select the previously selected run again after the runs changed, and
+    /// keep it at the same position on screen.
+    ///
+    /// If the run is gone, the run that took its place is selected.
+    fn restore_selection(&mut self) {
+        let old_idx = self.table_state.selected();
+        let new_idx = self
+            .selected_run
+            .and_then(|id| self.workflow_runs.iter().position(|r| r.id == id));
+
+        match (old_idx, new_idx) {
+            (Some(old_idx), Some(new_idx)) => {
+                let offset = self.table_state.offset();
+                *self.table_state.offset_mut() = (offset + new_idx).saturating_sub(old_idx);
+                self.table_state.select(Some(new_idx));
+            }
+            (Some(old_idx), None) => self.select(Some(old_idx)),
+            (None, _) => self.select(Some(0)),
+        }
+    }
+}
+
+type RepoKey = (String, String);
+
+fn repo_key(owner: &str, name: &str) -> RepoKey {
+    (owner.to_lowercase(), name.to_lowercase())
+}
+
+/// The refresh schedule of a repository.
+#[derive(Debug, Clone)]
+struct RepoRefresh {
+    last_refreshed: Option<time::Instant>,
+    retry_at: Option<time::Instant>,
+    backoff: Backoff,
+}
+
+impl Default for RepoRefresh {
+    fn default() -> Self {
+        Self {
+            last_refreshed: None,
+            retry_at: None,
+            backoff: Backoff::new(
+                WorkflowRunListWidget::RETRY_MIN_DELAY,
+                WorkflowRunListWidget::RETRY_MAX_DELAY,
+            ),
+        }
+    }
+}
+
+/// The result of fetching the runs of a repository.
+type RepoRuns = (Repository, Result<Vec<WorkflowRun>, ServiceError>);
+
+/// The result of expanding the sources into repositories.
+type Discovery = Result<Vec<Repository>, ServiceError>;
+
 impl Default for WorkflowRunListWidget {
     fn default() -> Self {
         Self {
             github_service: Arc::new(Service {}),
+            sources: vec![],
             repos: vec![],
+            discovered: false,
+            discovery_retry_at: None,
+            discovery_backoff: Backoff::new(Self::RETRY_MIN_DELAY, Self::RETRY_MAX_DELAY),
+            refresh_policy: RefreshPolicy::default(),
+            repo_refreshes: HashMap::new(),
+            history: None,
+            snapshots: None,
+            time_formatter: TimeFormatter::default(),
+            theme: Theme::default(),
+            layout_mode: LayoutMode::default(),
+            details_placement: DetailsPlacement::default(),
+            details_run: None,
+            details_focused: false,
+            pending_details: None,
+            footer: String::new(),
+            close_hint: String::new(),
+            browser: Browser::default(),
+            double_click: DoubleClick::default(),
             state: Arc::new(RwLock::new(WorkflowListState::default())),
             details_widget: Arc::new(RwLock::new(WorkflowDetailsWidget::default())),
+            runners_widget: RunnersWidget::default(),
+            usage_widget: UsageWidget::default(),
         }
     }
 }
 
 impl WorkflowRunListWidget {
-    pub fn new(github_service: Arc<dyn GitHubService>, repos: Vec<Repository>) -> Self {
+    /// How long the selection must stay on a run before its details are
+    /// fetched, when they follow the selection.
+    const DETAILS_DEBOUNCE: Duration = Duration::from_millis(300);
+    const DISCOVERY_INTERVAL: Duration = Duration::from_secs(15 * 60);
+    const ERROR_DURATION: Duration = Duration::from_secs(30);
+    const RETRY_MAX_DELAY: Duration = Duration::from_secs(60);
+    const RETRY_MIN_DELAY: Duration = Duration::from_secs(5);
+    /// How often the refresh interval of each repository is checked.
+    const SCHEDULER_TICK: Duration = Duration::from_secs(1);
+    const STATUS_DURATION: Duration = Duration::from_secs(5);
+
+    pub fn new(
+        github_service: Arc<dyn GitHubService>,
+        config: &Settings,
+        history: Option<Arc<HistoryStore>>,
+    ) -> Self {
+        let refresh_policy = RefreshPolicy::new(&config.refresh);
         let details_widget = Arc::new(RwLock::new(WorkflowDetailsWidget::new(
             github_service.clone(),
+            config,
+            history.clone(),
+            refresh_policy.clone(),
         )));
 
-        Self {
+        let close_hint = Keymap::new(&config.keys)
+            .hint(Action::Close, "to close")
+            .unwrap_or_default();
+        let runners_widget = RunnersWidget::new(
+            github_service.clone(),
+            refresh_policy.clone(),
+            Theme::new(&config.theme),
+            close_hint.clone(),
+        );
+        let usage_widget = UsageWidget::new(
+            github_service.clone(),
+            history.clone(),
+            Theme::new(&config.theme),
+            close_hint.clone(),
+        );
+
+        let snapshots = history.clone().map(|history| {
+            let (tx, rx) = mpsc::unbounded_channel();
+            tokio::spawn(save_snapshots(history, rx));
+            tx
+        });
+
+        let this = Self {
             github_service,
-            repos,
+            sources: config.repos.clone(),
+            refresh_policy,
+            history,
+            snapshots,
+            time_formatter: TimeFormatter::new(&config.time),
+            theme: Theme::new(&config.theme),
+            layout_mode: config.layout.mode,
+            details_placement: config.layout.details,
+            footer: footer(&Keymap::new(&config.keys)),
+            close_hint,
+            browser: Browser::new(&config.browser),
             details_widget,
+            runners_widget,
+            usage_widget,
             ..Default::default()
+        };
+        tokio::spawn(this.clone().load_snapshot());
+
+        this
+    }
+
+    /// Show the runs of the last session, marked as stale, until the first
+    /// refresh completes.
+    async fn load_snapshot(self) {
+        let Some(history) = &self.history else {
+            return;
+        };
+
+        let snapshot = history
+            .run_blocking(|h| Ok((h.latest_snapshot()?, h.latest_snapshot_time()?)))
+            .await;
+        let (runs, time) = match snapshot {
+            Ok((runs, Some(time))) => (runs, time),
+            Ok((_, None)) => return,
+            Err(err) => {
+                error!("Failed to load the latest snapshot: {:?}", err);
+                return;
+            }
+        };
+
+        let refreshed_at = time.with_timezone(&chrono::Local);
+
+        let mut state = self.state.write_or_recover();
+        // The first refresh completed while the snapshot was being loaded.
+        if state.refreshed_at.is_some() {
+            return;
         }
+        state.workflow_runs = runs;
+        state.select(Some(0));
+        state.refreshed_at = Some(refreshed_at);
+        state.loading_state = LoadingState::Stale {
+            refreshed_at,
+            retry_at: chrono::Local::now(),
+        };
     }
 
     /// Start fetching the pull requests in the background.
@@ -79,186 +343,1243 @@ impl WorkflowRunListWidget {
     /// This method spawns a background task that fetches the pull requests from
     /// the GitHub API. The result of the fetch is then passed to the
     /// `on_load` or `on_err` methods.
-    pub fn run(&self) -> mpsc::Sender<Event> {
+    pub fn run(&self) -> mpsc::Sender<Input> {
         let this = self.clone(); // clone the widget to pass to the background task
         let (tx, rx) = mpsc::channel(1024);
-        tokio::spawn(this.sync_data(rx));
+        // The receiver is handed over to the task again when it is restarted.
+        let rx = Arc::new(Mutex::new(rx));
+
+        let state = self.state.clone();
+        supervise(
+            "refresh",
+            move || this.clone().sync_data(rx.clone()),
+            move |message, delay| {
+                let mut state = state.write_or_recover();
+                state.loading_repos.clear();
+                state.loading_state = LoadingState::crashed(message, delay);
+            },
+        );
 
         tx
     }
 
-    async fn sync_data(mut self, mut rx: mpsc::Receiver<Event>) {
-        let period = Duration::from_secs(60);
-        let mut interval = time::interval(period);
+    /// The runs of each repository are fetched in their own task, and merged
+    /// into the state as soon as they are received, so that a slow repository
+    /// doesn't delay the others. The discovery runs in its own task too.
+    async fn sync_data(mut self, rx: Arc<Mutex<mpsc::Receiver<Input>>>) {
+        let mut rx = rx.lock().await;
+        let mut scheduler = time::interval(Self::SCHEDULER_TICK);
+        let mut discovery_interval = time::interval_at(
+            time::Instant::now() + Self::DISCOVERY_INTERVAL,
+            Self::DISCOVERY_INTERVAL,
+        );
+        let mut pending = JoinSet::new();
+        let mut discovery = JoinSet::new();
 
         loop {
+            let details_deadline = self
+                .pending_details
+                .map_or_else(time::Instant::now, |(t, _)| t);
+
             tokio::select! {
-                _ = interval.tick() => {
-                    self.fetch_workflow_runs().await;
+                _ = scheduler.tick() => {
+                    self.refresh_due(&mut pending, &mut discovery);
+                },
+                Some(res) = pending.join_next(), if !pending.is_empty() => {
+                    match res {
+                        Ok((repo, runs)) => self.on_repo_runs(repo, runs),
+                        Err(err) => error!("Failed to get workflow runs: {:?}", err),
+                    }
                 },
-                Some(event) = rx.recv() => {
-                    self.handle_event(&event).await
+                Some(res) = discovery.join_next(), if !discovery.is_empty() => {
+                    match res {
+                        Ok(repos) => self.on_discovery(repos),
+                        Err(err) => {
+                            error!("Failed to discover the repositories: {:?}", err);
+                            self.on_discovery_failure();
+                        }
+                    }
+                    // The new repositories don't wait for the next tick.
+                    self.refresh_due(&mut pending, &mut discovery);
+                },
+                _ = discovery_interval.tick() => {
+                    self.discover_repos(&mut discovery);
+                },
+                Some(input) = rx.recv() => {
+                    self.handle_event(&input).await
+                },
+                _ = time::sleep_until(details_deadline), if self.pending_details.is_some() => {
+                    self.pending_details = None;
+                    // The statistics cost a request per run, they aren't
+                    // computed while browsing.
+                    self.show_details(false);
                 },
             }
+
+            self.follow_selection();
         }
     }
 
-    async fn handle_event(&mut self, event: &Event) {
-        if let Some(key) = event.as_key_press_event() {
-            match key.code {
-                KeyCode::Enter => self.open_url(),
-                KeyCode::Char('d') => self.show_details(),
-                KeyCode::Char('j') | KeyCode::Down => self.scroll_down(),
-                KeyCode::Char('k') | KeyCode::Up => self.scroll_up(),
-                KeyCode::Char('r') => self.fetch_workflow_runs().await,
-                KeyCode::Esc => self.hide_details().await,
+    /// Show the details of the selected run when they are next to the runs.
+    ///
+    /// The details are only fetched once the selection settles, so that
+    /// scrolling through the runs doesn't fetch the jobs of every run.
+    fn follow_selection(&mut self) {
+        // The wallboard has no details.
+        if self.details_placement == DetailsPlacement::Popup
+            || self.layout_mode == LayoutMode::Wallboard
+        {
+            return;
+        }
+
+        let selected = self.state.read_or_recover().selected_run;
+
+        if selected.is_none() || selected == self.details_run {
+            self.pending_details = None;
+            return;
+        }
+
+        if self
+            .pending_details
+            .is_none_or(|(_, id)| Some(id) != selected)
+        {
+            let deadline = time::Instant::now() + Self::DETAILS_DEBOUNCE;
+            self.pending_details = selected.map(|id| (deadline, id));
+        }
+    }
+
+    async fn handle_event(&mut self, input: &Input) {
+        match input {
+            Input::Event(Event::FocusGained) => self.refresh_policy.set_focused(true),
+            Input::Event(Event::FocusLost) => self.refresh_policy.set_focused(false),
+            Input::Event(event @ Event::Mouse(mouse)) => self.handle_mouse(event, mouse).await,
+            Input::Action(action) => self.handle_action(*action).await,
+            _ => {}
+        }
+    }
+
+    /// A click selects a run and a double-click shows its details. The events
+    /// within the details go to the details, and while they are shown as a
+    /// popup, a click outside closes it.
+    async fn handle_mouse(&mut self, event: &Event, mouse: &MouseEvent) {
+        let position = Position::new(mouse.column, mouse.row);
+
+        // The wheel scrolls the usage, the runners or the queue while they
+        // are shown.
+        if self.usage_widget.is_visible() {
+            match mouse.kind {
+                MouseEventKind::ScrollDown => self.usage_widget.scroll_by(1),
+                MouseEventKind::ScrollUp => self.usage_widget.scroll_by(-1),
                 _ => {}
             }
+            return;
         }
+        if self.runners_widget.view().is_some() {
+            match mouse.kind {
+                MouseEventKind::ScrollDown => self.runners_widget.scroll_by(1),
+                MouseEventKind::ScrollUp => self.runners_widget.scroll_by(-1),
+                _ => {}
+            }
+            return;
+        }
+
+        let details_area = self.state.read_or_recover().details_area;
+        if let Some(details_area) = details_area {
+            if details_area.contains(position) {
+                if let MouseEventKind::Down(MouseButton::Left) = mouse.kind {
+                    self.set_details_focused(true);
+                }
+                self.details_widget.read_or_recover().handle_event(event);
+                return;
+            }
+
+            if self.details_placement == DetailsPlacement::Popup {
+                if let MouseEventKind::Down(MouseButton::Left) = mouse.kind {
+                    self.hide_details().await;
+                }
+                return;
+            }
+        }
+
+        match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                let row = {
+                    let state = self.state.read_or_recover();
+                    let offset = state.table_state.offset();
+                    row_at(
+                        state.table_area,
+                        state.header_height,
+                        offset,
+                        state.row_height,
+                        position,
+                    )
+                    .filter(|r| *r < state.workflow_runs.len())
+                };
+                let Some(row) = row else {
+                    return;
+                };
+
+                self.set_details_focused(false);
+                self.state.write_or_recover().select(Some(row));
+                if self.double_click.click(row) {
+                    self.show_details(true);
+                }
+            }
+            MouseEventKind::ScrollDown => self.scroll_by(1),
+            MouseEventKind::ScrollUp => self.scroll_by(-1),
+            _ => {}
+        }
+    }
+
+    /// Whether the keys go to the jobs: the popup always has the focus, the
+    /// details next to the runs get it with `SwitchFocus` or a click.
+    fn details_have_focus(&self) -> bool {
+        self.details_widget.read_or_recover().is_visible()
+            && (self.details_placement == DetailsPlacement::Popup || self.details_focused)
+    }
+
+    fn set_details_focused(&mut self, focused: bool) {
+        self.details_focused = focused;
+        self.details_widget
+            .read_or_recover()
+            .set_focused(self.details_have_focus());
     }
 
-    async fn fetch_workflow_runs(&self) {
-        self.set_loading_state(LoadingState::Loading);
+    async fn handle_action(&mut self, action: Action) {
+        // The flaky jobs are shown over the runs and the details.
+        if matches!(action, Action::Close | Action::FlakyJobs) {
+            let mut state = self.state.write_or_recover();
+            if state.flaky_jobs.take().is_some() {
+                return;
+            }
+        }
+
+        // So is the usage, it gets the keys to scroll.
+        if self.usage_widget.is_visible() {
+            match action {
+                Action::Usage | Action::Close => {
+                    self.usage_widget.hide();
+                }
+                Action::Refresh => self.show_usage(),
+                Action::ScrollDown => self.usage_widget.scroll_by(1),
+                Action::ScrollUp => self.usage_widget.scroll_by(-1),
+                Action::PageDown => self.usage_widget.scroll_by(self.usage_widget.page_size()),
+                Action::PageUp => self.usage_widget.scroll_by(-self.usage_widget.page_size()),
+                _ => {}
+            }
+            return;
+        }
+
+        // So are the runners and the queue, they get the keys to scroll.
+        if let Some(view) = self.runners_widget.view() {
+            match action {
+                Action::Runners | Action::Queue if self.runners_view(action) != Some(view) => {
+                    self.show_runners(action);
+                }
+                Action::Runners | Action::Queue | Action::Close => {
+                    self.runners_widget.hide();
+                }
+                Action::ScrollDown => self.runners_widget.scroll_by(1),
+                Action::ScrollUp => self.runners_widget.scroll_by(-1),
+                Action::PageDown => self
+                    .runners_widget
+                    .scroll_by(self.runners_widget.page_size()),
+                Action::PageUp => self
+                    .runners_widget
+                    .scroll_by(-self.runners_widget.page_size()),
+                _ => {}
+            }
+            return;
+        }
+
+        if self.details_have_focus() {
+            match action {
+                Action::Close if self.details_widget.read_or_recover().show_jobs() => {}
+                Action::SwitchFocus | Action::Close
+                    if self.details_placement != DetailsPlacement::Popup =>
+                {
+                    self.set_details_focused(false);
+                }
+                Action::Close => self.hide_details().await,
+                // The jobs have nothing to copy but their URL.
+                Action::Refresh
+                | Action::Details
+                | Action::FlakyJobs
+                | Action::Runners
+                | Action::Queue
+                | Action::Usage
+                | Action::CopySha
+                | Action::CopyBranch
+                | Action::CopySummary => self.handle_list_action(action).await,
+                action => self.details_widget.read_or_recover().handle_action(action),
+            }
+            return;
+        }
 
-        let workflows = self.github_service.list_runs(&self.repos).await;
+        match action {
+            Action::SwitchFocus if self.details_widget.read_or_recover().is_visible() => {
+                self.set_details_focused(true);
+            }
+            action => self.handle_list_action(action).await,
+        }
+    }
 
-        match workflows {
-            Ok(wfs) => self.on_load(wfs),
-            Err(err) => self.on_err(&err),
+    async fn handle_list_action(&mut self, action: Action) {
+        match action {
+            Action::Open => self.open_url(),
+            Action::CopyUrl => self.copy(|r| r.html_url.to_string()),
+            Action::CopySha => self.copy(|r| r.head_sha.clone()),
+            Action::CopyBranch => self.copy(|r| r.branch.clone()),
+            Action::CopySummary => self.copy(WorkflowRun::summary),
+            Action::Details => self.show_details(true),
+            Action::ScrollDown => self.scroll_by(1),
+            Action::ScrollUp => self.scroll_by(-1),
+            Action::PageDown => self.scroll_by(self.page_size()),
+            Action::PageUp => self.scroll_by(-self.page_size()),
+            Action::First => self.state.write_or_recover().select(Some(0)),
+            Action::Last => self.state.write_or_recover().select(Some(usize::MAX)),
+            Action::Refresh => self.refresh_all(),
+            Action::FlakyJobs => self.show_flaky_jobs(),
+            Action::Runners | Action::Queue => self.show_runners(action),
+            Action::Usage => self.show_usage(),
+            Action::Close => self.hide_details().await,
+            Action::Quit
+            | Action::Help
+            | Action::SwitchFocus
+            | Action::RerunJob
+            | Action::Artifacts
+            | Action::TestResults
+            | Action::Download => {}
         }
     }
 
-    fn on_load(&self, runs: Vec<WorkflowRun>) {
-        let mut state = self.state.write().unwrap();
+    /// Make every repository due, they are refreshed on the next scheduler
+    /// tick.
+    fn refresh_all(&mut self) {
+        self.repo_refreshes.clear();
+        self.discovery_retry_at = None;
+    }
 
-        state.workflow_runs = runs;
+    /// Start fetching the workflow runs of the repositories whose refresh
+    /// interval has elapsed, discovering the repositories first if it has
+    /// never succeeded.
+    fn refresh_due(&mut self, pending: &mut JoinSet<RepoRuns>, discovery: &mut JoinSet<Discovery>) {
+        let now = time::Instant::now();
 
-        if !state.workflow_runs.is_empty() && state.table_state.selected().is_none() {
-            state.table_state.select(Some(0));
+        if !self.discovered {
+            if self.discovery_retry_at.is_none_or(|t| t <= now) {
+                self.discover_repos(discovery);
+            }
+            return;
         }
 
-        state.loading_state = LoadingState::Loaded(chrono::Local::now());
+        let loading = self.state.read_or_recover().loading_repos.clone();
+        let due: Vec<Repository> = self
+            .repos
+            .iter()
+            .filter(|r| !loading.contains(&repo_key(&r.owner, &r.name)))
+            .filter(|r| self.next_refresh(r).is_none_or(|t| t <= now))
+            .cloned()
+            .collect();
+
+        if !due.is_empty() {
+            let mut state = self.state.write_or_recover();
+
+            due.into_iter().for_each(|repo| {
+                state
+                    .loading_repos
+                    .insert(repo_key(&repo.owner, &repo.name));
+
+                let github_service = self.github_service.clone();
+                pending.spawn(async move {
+                    let runs = github_service.list_runs(&repo).await;
+                    (repo, runs)
+                });
+            });
+        }
+
+        self.update_loading_state();
+    }
+
+    /// When the repository is next due for a refresh, `None` if it never was
+    /// refreshed.
+    fn next_refresh(&self, repo: &Repository) -> Option<time::Instant> {
+        let refresh = self
+            .repo_refreshes
+            .get(&repo_key(&repo.owner, &repo.name))?;
+
+        if let Some(retry_at) = refresh.retry_at {
+            return Some(retry_at);
+        }
+
+        let running = self
+            .state
+            .read_or_recover()
+            .workflow_runs
+            .iter()
+            .any(|r| repo.owns(r) && !r.is_completed());
+
+        refresh
+            .last_refreshed
+            .map(|t| t + self.refresh_policy.interval(repo.refresh_interval, running))
+    }
+
+    /// Start expanding the configured sources into the concrete repositories
+    /// to watch, unless a discovery is already running.
+    fn discover_repos(&self, discovery: &mut JoinSet<Discovery>) {
+        if !discovery.is_empty() {
+            return;
+        }
+
+        let github_service = self.github_service.clone();
+        let sources = self.sources.clone();
+        discovery.spawn(async move { github_service.discover_repos(&sources).await });
+    }
+
+    /// Watch the discovered repositories, the previously discovered ones are
+    /// kept if the discovery failed.
+    fn on_discovery(&mut self, repos: Discovery) {
+        match repos {
+            Ok(repos) => {
+                self.repos = repos;
+                self.forget_removed_repos();
+                self.discovered = true;
+                self.discovery_retry_at = None;
+                self.discovery_backoff.reset();
+            }
+            Err(err) => {
+                self.on_err(&err);
+                self.on_discovery_failure();
+            }
+        }
+    }
+
+    /// Retry the first discovery with a backoff, the next ones wait for the
+    /// discovery interval.
+    fn on_discovery_failure(&mut self) {
+        if self.discovered {
+            return;
+        }
+
+        let delay = self.discovery_backoff.next_delay();
+        self.discovery_retry_at = Some(time::Instant::now() + delay);
+        self.set_next_refresh(delay);
+    }
+
+    /// Drop the runs and the refresh schedule of the repositories that are no
+    /// longer watched, e.g. removed from the organization or now excluded.
+    fn forget_removed_repos(&mut self) {
+        let watched: HashSet<RepoKey> = self
+            .repos
+            .iter()
+            .map(|r| repo_key(&r.owner, &r.name))
+            .collect();
+        self.repo_refreshes.retain(|key, _| watched.contains(key));
+
+        let mut state = self.state.write_or_recover();
+        state.loading_repos.retain(|key| watched.contains(key));
+
+        let count = state.workflow_runs.len();
+        state
+            .workflow_runs
+            .retain(|r| watched.contains(&repo_key(&r.owner, &r.repo)));
+        if state.workflow_runs.len() == count {
+            return;
+        }
+
+        state.restore_selection();
+
+        let runs = state.workflow_runs.clone();
+        drop(state);
+        self.save_snapshot(runs);
+    }
+
+    fn on_repo_runs(&mut self, repo: Repository, runs: Result<Vec<WorkflowRun>, ServiceError>) {
+        let key = repo_key(&repo.owner, &repo.name);
+        self.state.write_or_recover().loading_repos.remove(&key);
+
+        // The repository may have been dropped by a discovery while its runs
+        // were being fetched.
+        if !self
+            .repos
+            .iter()
+            .any(|r| repo_key(&r.owner, &r.name) == key)
+        {
+            return;
+        }
+
+        let now = time::Instant::now();
+        let refresh = self.repo_refreshes.entry(key).or_default();
+
+        match runs {
+            Ok(runs) => {
+                refresh.last_refreshed = Some(now);
+                refresh.retry_at = None;
+                refresh.backoff.reset();
+
+                self.on_load(&repo, runs);
+            }
+            Err(err) => {
+                // Retry sooner than the refresh interval, until the network is back.
+                let delay = refresh.backoff.next_delay();
+                refresh.retry_at = Some(now + delay);
+
+                error!(
+                    "Failed to get workflow runs for {}/{}, retrying in {:?}: {}",
+                    repo.owner, repo.name, delay, err
+                );
+                self.set_loading_state(LoadingState::Error(err.to_string()));
+            }
+        }
+
+        self.update_loading_state();
+    }
+
+    /// Merge the runs of a repository into the state.
+    ///
+    /// The runs are matched by id, so that the selection and the scroll
+    /// position stay on the same run when runs are added or removed.
+    fn on_load(&self, repo: &Repository, runs: Vec<WorkflowRun>) {
+        let mut state = self.state.write_or_recover();
+
+        state
+            .workflow_runs
+            .retain(|r| !repo.owns(r) || runs.iter().any(|n| n.id == r.id));
+
+        for run in runs {
+            match state.workflow_runs.iter_mut().find(|r| r.id == run.id) {
+                Some(existing) => *existing = run,
+                None => state.workflow_runs.push(run),
+            }
+        }
+
+        state
+            .workflow_runs
+            .sort_by(|a, b| Ord::cmp(&a.start_time, &b.start_time).reverse());
+
+        state.restore_selection();
+
+        state.refreshed_at = Some(chrono::Local::now());
+
+        let runs = state.workflow_runs.clone();
+        drop(state);
+        self.save_snapshot(runs);
+    }
+
+    /// Queue the runs to be saved as the latest snapshot.
+    fn save_snapshot(&self, runs: Vec<WorkflowRun>) {
+        if let Some(snapshots) = &self.snapshots
+            && snapshots.send(runs).is_err()
+        {
+            error!("Failed to save workflow runs: the history writer has stopped");
+        }
     }
 
     fn on_err(&self, err: &Exn<ServiceError>) {
         self.set_loading_state(LoadingState::Error(err.to_string()));
     }
 
+    /// Derive the loading state from the state of each repository.
+    ///
+    /// The runs are stale as long as a repository couldn't be refreshed, and
+    /// the last known runs keep being displayed while retrying.
+    fn update_loading_state(&self) {
+        let retry_at = self
+            .repo_refreshes
+            .values()
+            .filter_map(|r| r.retry_at)
+            .min();
+        let next_refresh = self.repos.iter().filter_map(|r| self.next_refresh(r)).min();
+
+        let mut state = self.state.write_or_recover();
+
+        if let Some(next) = next_refresh {
+            let delay = next.saturating_duration_since(time::Instant::now());
+            state.next_refresh_at = chrono::TimeDelta::from_std(delay)
+                .ok()
+                .map(|d| chrono::Local::now() + d);
+        }
+
+        state.loading_state = match (retry_at, state.refreshed_at) {
+            _ if !state.loading_repos.is_empty() => LoadingState::Loading,
+            (Some(retry_at), Some(refreshed_at)) => {
+                let delay = retry_at.saturating_duration_since(time::Instant::now());
+                LoadingState::Stale {
+                    refreshed_at,
+                    retry_at: chrono::Local::now()
+                        + chrono::TimeDelta::from_std(delay).unwrap_or_default(),
+                }
+            }
+            // Keep the error, there is nothing to show.
+            (Some(_), None) => return,
+            (None, Some(refreshed_at)) => LoadingState::Loaded(refreshed_at),
+            (None, None) => LoadingState::Idle,
+        };
+    }
+
+    fn set_next_refresh(&self, delay: Duration) {
+        self.state.write_or_recover().next_refresh_at = chrono::TimeDelta::from_std(delay)
+            .ok()
+            .map(|d| chrono::Local::now() + d);
+    }
+
     fn set_loading_state(&self, state: LoadingState) {
-        self.state.write().unwrap().loading_state = state;
+        self.state.write_or_recover().loading_state = state;
     }
 
-    fn scroll_down(&self) {
-        self.state.write().unwrap().table_state.scroll_down_by(1);
+    fn scroll_by(&self, rows: isize) {
+        let mut state = self.state.write_or_recover();
+        let idx = state
+            .table_state
+            .selected()
+            .map_or(0, |i| i.saturating_add_signed(rows));
+        state.select(Some(idx));
     }
 
-    fn scroll_up(&self) {
-        self.state.write().unwrap().table_state.scroll_up_by(1);
+    /// The number of rows visible in the table, as of the last render.
+    fn page_size(&self) -> isize {
+        self.state.read_or_recover().page_size.max(1) as isize
     }
 
-    fn show_details(&self) {
-        let state = self.state.read().unwrap();
+    /// Show the details of the selected run, and compute the statistics of
+    /// its workflow if `fetch_stats`, otherwise they are only shown when they
+    /// were computed recently.
+    fn show_details(&mut self, fetch_stats: bool) {
+        let state = self.state.read_or_recover();
 
-        let idx = match state.table_state.selected() {
-            Some(idx) => idx,
-            None => return,
+        let Some(workflow) = state.selected_run().cloned() else {
+            return;
         };
 
-        let workflow = state.workflow_runs[idx].clone();
-
-        let mut w = self.details_widget.write().unwrap();
+        self.details_run = Some(workflow.id);
+        let mut w = self.details_widget.write_or_recover();
 
         w.hide(); // Hide / stop any previous details widget
 
         w.show();
-        w.run(workflow);
+        w.run(workflow, fetch_stats);
+        drop(w);
+        drop(state);
+
+        self.set_details_focused(self.details_focused);
     }
 
-    async fn hide_details(&self) {
-        self.details_widget.write().unwrap().hide();
+    async fn hide_details(&mut self) {
+        // The details next to the runs are always shown.
+        if self.details_placement != DetailsPlacement::Popup {
+            return;
+        }
+
+        self.details_run = None;
+        self.details_widget.write_or_recover().hide();
+        self.set_details_focused(false);
+    }
+
+    /// Show the flakiest jobs of the repository of the selected run, from the
+    /// jobs in the history store: only the runs whose jobs were fetched, e.g.
+    /// for their details or the statistics, are known.
+    fn show_flaky_jobs(&self) {
+        let Some(history) = self.history.clone() else {
+            self.set_status("The flaky jobs need the history".to_string(), true);
+            return;
+        };
+
+        let Some((owner, repo)) = self
+            .state
+            .read_or_recover()
+            .selected_run()
+            .map(|r| (r.owner.clone(), r.repo.clone()))
+        else {
+            return;
+        };
+
+        // The jobs of a busy repository take a while to read.
+        let this = self.clone();
+        tokio::spawn(async move { this.load_flaky_jobs(history, owner, repo).await });
+    }
+
+    async fn load_flaky_jobs(&self, history: Arc<HistoryStore>, owner: String, repo: String) {
+        let (o, r) = (owner.clone(), repo.clone());
+        let flaky_jobs = history.run_blocking(move |h| {
+            let jobs = h.repo_jobs(&o, &r)?;
+            let observed_runs: HashSet<_> = jobs.iter().map(|(_, j)| j.run_id).collect();
+            Ok((observed_runs.len(), flaky::detect(&jobs)))
+        });
+
+        match flaky_jobs.await {
+            Ok((observed_runs, jobs)) => {
+                self.state.write_or_recover().flaky_jobs = Some(FlakyJobs {
+                    repo: format!("{}/{}", owner, repo),
+                    observed_runs,
+                    jobs,
+                });
+            }
+            Err(err) => {
+                error!("Failed to load the jobs of {}/{}: {:?}", owner, repo, err);
+                self.set_status("Unable to load the jobs".to_string(), true);
+            }
+        }
+    }
+
+    fn runners_view(&self, action: Action) -> Option<RunnersView> {
+        match action {
+            Action::Runners => Some(RunnersView::Runners),
+            Action::Queue => Some(RunnersView::Queue),
+            _ => None,
+        }
+    }
+
+    /// The organizations of the discovered repositories. Only the owners of the
+    /// organizations and the patterns are known to be organizations, the owner
+    /// of a repository may be a user.
+    fn organizations(&self) -> Vec<String> {
+        let orgs: Vec<_> = self
+            .sources
+            .iter()
+            .filter_map(|s| match s {
+                RepositorySource::Organization(org) => Some(org.org.as_str()),
+                RepositorySource::Pattern(pattern) => pattern.split().map(|(owner, _)| owner),
+                RepositorySource::Repository(_) => None,
+            })
+            .collect();
+
+        let mut seen = HashSet::new();
+        self.repos
+            .iter()
+            .filter(|r| orgs.iter().any(|o| o.eq_ignore_ascii_case(&r.owner)))
+            .filter(|r| seen.insert(r.owner.to_lowercase()))
+            .map(|r| r.owner.clone())
+            .collect()
+    }
+
+    /// Show the runners of the organizations and of the repositories, or the
+    /// jobs of the runs still going that wait for one.
+    fn show_runners(&self, action: Action) {
+        let Some(view) = self.runners_view(action) else {
+            return;
+        };
+
+        let mut scopes: Vec<_> = self
+            .organizations()
+            .into_iter()
+            .map(RunnerScope::Organization)
+            .collect();
+        scopes.extend(self.repos.iter().map(|r| RunnerScope::Repository {
+            owner: r.owner.clone(),
+            name: r.name.clone(),
+        }));
+
+        let state = self.state.clone();
+        let active_runs = Arc::new(move || {
+            state
+                .read_or_recover()
+                .workflow_runs
+                .iter()
+                .filter(|r| !r.is_completed())
+                .cloned()
+                .collect()
+        });
+
+        self.runners_widget.show(view, scopes, active_runs);
+    }
+
+    /// Show the Actions minutes of the workflows of the runs.
+    fn show_usage(&self) {
+        let mut seen = HashSet::new();
+        let runs = self
+            .state
+            .read_or_recover()
+            .workflow_runs
+            .iter()
+            .filter(|r| seen.insert((r.owner.clone(), r.repo.clone(), r.workflow_id)))
+            .cloned()
+            .collect();
+
+        self.usage_widget.show(runs);
+    }
+
+    /// Show a message at the bottom of the runs, the errors stay longer.
+    fn set_status(&self, text: String, is_error: bool) {
+        let duration = match is_error {
+            true => Self::ERROR_DURATION,
+            false => Self::STATUS_DURATION,
+        };
+
+        self.state.write_or_recover().status = Some(StatusMessage {
+            text,
+            is_error,
+            expires_at: time::Instant::now() + duration,
+        });
     }
 
     fn open_url(&self) {
-        let state = self.state.read().unwrap();
-        let idx = match state.table_state.selected() {
-            Some(idx) => idx,
-            None => return,
+        let url = {
+            let state = self.state.read_or_recover();
+            let Some(run) = state.selected_run() else {
+                return;
+            };
+            run.html_url.to_string()
         };
 
-        let url = &state.workflow_runs[idx].html_url;
-        open::that(url.as_str()).unwrap();
+        // The URL can still be copied, or clicked in the terminal.
+        if let Err(err) = self.browser.open(&url) {
+            error!("Failed to open the run: {:?}", err);
+            self.set_status(format!("Unable to open a browser: {}", url), true);
+        }
+    }
+
+    /// Copy some text about the selected run to the clipboard.
+    fn copy(&self, text: impl FnOnce(&WorkflowRun) -> String) {
+        let text = {
+            let state = self.state.read_or_recover();
+            let Some(run) = state.selected_run() else {
+                return;
+            };
+            text(run)
+        };
+
+        match clipboard::copy(&text) {
+            Ok(()) => self.set_status(format!("Copied {}", text), false),
+            Err(err) => {
+                error!("Failed to copy to the clipboard: {:?}", err);
+                self.set_status("Unable to copy to the clipboard".to_string(), true);
+            }
+        }
     }
 }
 
 impl Widget for &WorkflowRunListWidget {
     fn render(self, area: Rect, buf: &mut Buffer) {
-        let mut state = self.state.write().unwrap();
+        let mut state = self.state.write_or_recover();
+
+        let split = match (self.layout_mode, self.details_placement) {
+            (LayoutMode::Wallboard, _) | (_, DetailsPlacement::Popup) => None,
+            (_, DetailsPlacement::Right) => Some(Layout::horizontal([
+                Constraint::Percentage(55),
+                Constraint::Percentage(45),
+            ])),
+            (_, DetailsPlacement::Bottom) => Some(Layout::vertical([
+                Constraint::Percentage(50),
+                Constraint::Percentage(50),
+            ])),
+        };
+        let (area, details_area) = match split {
+            Some(split) => {
+                let [area, details_area] = area.layout(&split);
+                (area, Some(details_area))
+            }
+            None => (area, None),
+        };
+        state.details_area = details_area;
 
         // a block with a right aligned title with the loading state on the right
-        let loading_state = Line::from(format!("{}", state.loading_state)).right_aligned();
-        let block = Block::bordered()
+        let mut loading_state = self.time_formatter.loading_state(&state.loading_state);
+        if let (LoadingState::Loaded(_), Some(next)) = (&state.loading_state, state.next_refresh_at)
+        {
+            let remaining = next - chrono::Local::now();
+            loading_state = format!("{}, next in {}", loading_state, format_duration(remaining));
+        }
+        let loading_state = Line::from(loading_state)
+            .style(self.theme.loading_state(&state.loading_state))
+            .right_aligned();
+        let mut block = Block::bordered()
             .title("Workflow Runs")
             .title(loading_state)
-            .title_bottom("j/k to scroll, q to quit");
-
-        // a table with the list of workflow runs
-        let widths = [
-            Constraint::Max(50),    // Project
-            Constraint::Max(32),    // Branch Name
-            Constraint::Max(32),    // Workflow Name
-            Constraint::Max(128),   // Commit Title
-            Constraint::Max(32),    // Start Time
-            Constraint::Length(16), // Status
-            Constraint::Length(16), // Completion
-        ];
-
-        let header = Row::new(vec![
-            "Project",
-            "Branch",
-            "Workflow Name",
-            "Commit Title",
-            "Start Time",
-            "Status",
-            "Completion",
-        ])
-        .style(Style::new().bold());
+            .title_bottom(self.footer.as_str());
+        if let Some(status) = state
+            .status
+            .clone()
+            .filter(|s| time::Instant::now() < s.expires_at)
+        {
+            let style = match status.is_error {
+                true => self.theme.failure_style(),
+                false => self.theme.success_style(),
+            };
+            block = block.title_bottom(Line::from(status.text).style(style).right_aligned());
+        }
+        // Show which of the runs and the details next to them has the focus.
+        if details_area.is_some() && !self.details_focused {
+            block = block.border_type(BorderType::Thick);
+        }
+
+        let mode = layout::resolve(self.layout_mode, area);
+        let inner = block.inner(area);
+
+        if mode == LayoutMode::Wallboard {
+            // Nothing can be selected on the wallboard.
+            state.table_area = Rect::default();
+            block.render(area, buf);
+            layout::render_wallboard(
+                &state.workflow_runs,
+                &self.time_formatter,
+                &self.theme,
+                inner,
+                buf,
+            );
+            return;
+        }
 
-        let rows = state.workflow_runs.iter();
+        // The cards have no header.
+        (state.row_height, state.header_height) = match mode {
+            LayoutMode::Cards => (layout::CARD_HEIGHT, 0),
+            _ => (1, 1),
+        };
+        // The borders and the header aren't part of a page.
+        state.page_size =
+            usize::from(area.height.saturating_sub(2 + state.header_height) / state.row_height);
+        state.table_area = area;
+
+        let is_loading =
+            |r: &WorkflowRun| state.loading_repos.contains(&repo_key(&r.owner, &r.repo));
+
+        let table = if mode == LayoutMode::Cards {
+            let rows = state
+                .workflow_runs
+                .iter()
+                .map(|r| layout::card_row(r, is_loading(r), &self.time_formatter, &self.theme));
+
+            Table::new(rows, [Constraint::Fill(1)])
+        } else {
+            let columns = Columns::new(inner.width);
+            let rows = state
+                .workflow_runs
+                .iter()
+                .map(|r| columns.row(r, is_loading(r), &self.time_formatter, &self.theme));
+
+            Table::new(rows, columns.widths()).header(columns.header().style(self.theme.header))
+        };
 
-        let table = Table::new(rows, widths)
-            .header(header)
+        let table = table
             .block(block)
             .highlight_spacing(HighlightSpacing::Always)
             .highlight_symbol(">>")
-            .row_highlight_style(Style::new().on_blue());
+            .row_highlight_style(self.theme.highlight);
 
         StatefulWidget::render(table, area, buf, &mut state.table_state);
 
-        let details_widget = self.details_widget.read().unwrap();
-        if details_widget.is_visible() {
+        let details_widget = self.details_widget.read_or_recover();
+        if let Some(details_area) = details_area {
+            Widget::render(details_widget.deref(), details_area, buf);
+        } else if details_widget.is_visible() {
             let centered_area =
                 area.centered(Constraint::Percentage(75), Constraint::Percentage(75));
 
+            state.details_area = Some(centered_area);
             Widget::render(Clear, centered_area, buf);
             Widget::render(details_widget.deref(), centered_area, buf);
         }
+
+        if let Some(flaky_jobs) = &state.flaky_jobs {
+            let popup_area = area.centered(Constraint::Percentage(80), Constraint::Percentage(60));
+            Widget::render(Clear, popup_area, buf);
+            render_flaky_jobs(flaky_jobs, &self.close_hint, &self.theme, popup_area, buf);
+        }
+
+        if self.runners_widget.view().is_some() {
+            let popup_area = area.centered(Constraint::Percentage(90), Constraint::Percentage(70));
+            Widget::render(Clear, popup_area, buf);
+            Widget::render(&self.runners_widget, popup_area, buf);
+        }
+
+        if self.usage_widget.is_visible() {
+            let popup_area = area.centered(Constraint::Percentage(90), Constraint::Percentage(70));
+            Widget::render(Clear, popup_area, buf);
+            Widget::render(&self.usage_widget, popup_area, buf);
+        }
+    }
+}
+
+/// Save the snapshots in the history, one at a time and in order, off the
+/// async workers. Only the latest of the snapshots queued while one is saved
+/// is saved. The runs past the retention period are pruned periodically.
+async fn save_snapshots(
+    history: Arc<HistoryStore>,
+    mut snapshots: mpsc::UnboundedReceiver<Vec<WorkflowRun>>,
+) {
+    // The history is pruned when it is opened.
+    let mut prune_interval =
+        time::interval_at(time::Instant::now() + PRUNE_INTERVAL, PRUNE_INTERVAL);
+
+    loop {
+        let res = tokio::select! {
+            runs = snapshots.recv() => {
+                let Some(mut runs) = runs else {
+                    return;
+                };
+                while let Ok(newer) = snapshots.try_recv() {
+                    runs = newer;
+                }
+
+                history.run_blocking(move |h| h.save_snapshot(&runs)).await
+            },
+            _ = prune_interval.tick() => {
+                history.run_blocking(HistoryStore::prune).await
+            },
+        };
+
+        if let Err(err) = res {
+            error!("Failed to update the history: {:?}", err);
+        }
     }
 }
 
-impl From<&WorkflowRun> for Row<'_> {
-    fn from(r: &WorkflowRun) -> Self {
-        let r = r.clone();
+/// How often the history is pruned.
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+fn render_flaky_jobs(
+    flaky_jobs: &FlakyJobs,
+    close_hint: &str,
+    theme: &Theme,
+    area: Rect,
+    buf: &mut Buffer,
+) {
+    let block = Block::bordered()
+        .title(format!(
+            "Flaky Jobs of {} (in the {} runs observed locally)",
+            flaky_jobs.repo, flaky_jobs.observed_runs
+        ))
+        .title_bottom(close_hint);
+
+    if flaky_jobs.jobs.is_empty() {
+        Paragraph::new("No job failed then passed on the same commit")
+            .block(block)
+            .render(area, buf);
+        return;
+    }
+
+    let rows = flaky_jobs.jobs.iter().map(|j| {
         Row::new(vec![
-            format!("{}/{}", r.owner, r.repo),
-            r.branch,
-            r.name,
-            r.commit_message.split('\n').next().unwrap().to_string(),
-            r.start_time
-                .with_timezone(&chrono::Local)
-                .format("%Y-%m-%d %H:%M:%S")
-                .to_string(),
-            r.status.to_string(),
-            r.conclusion.to_string(),
+            j.workflow.clone(),
+            j.name.clone(),
+            j.flakes.to_string(),
+            j.executions.to_string(),
+            format!("{:.0}%", j.rate() * 100.0),
         ])
+    });
+    let header = Row::new(vec!["Workflow", "Job", "Flakes", "Runs", "Rate"]).style(theme.header);
+    let widths = [
+        Constraint::Max(32),
+        Constraint::Fill(1),
+        Constraint::Length(8),
+        Constraint::Length(8),
+        Constraint::Length(8),
+    ];
+
+    Widget::render(
+        Table::new(rows, widths).header(header).block(block),
+        area,
+        buf,
+    );
+}
+
+fn footer(keymap: &Keymap) -> String {
+    let hints: Vec<_> = [(Action::Help, "for help"), (Action::Quit, "to quit")]
+        .iter()
+        .filter_map(|(action, text)| keymap.hint(*action, text))
+        .collect();
+
+    hints.join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+    use fake::{Fake, Faker};
+
+    use super::*;
+    use crate::models::WorkflowRunStatus;
+    use crate::service::workflows::MockGitHubService;
+
+    fn repo(owner: &str, name: &str) -> Repository {
+        Repository {
+            owner: owner.to_string(),
+            name: name.to_string(),
+            branch: None,
+            count: None,
+            actor: None,
+            refresh_interval: None,
+        }
+    }
+
+    fn run(id: u64, repo: &Repository) -> WorkflowRun {
+        WorkflowRun {
+            id: RunId(id),
+            owner: repo.owner.clone(),
+            repo: repo.name.clone(),
+            start_time: Utc.timestamp_opt(id as i64, 0).unwrap(),
+            status: WorkflowRunStatus::Completed,
+            ..Faker.fake()
+        }
+    }
+
+    fn widget(github_service: MockGitHubService, repos: &[Repository]) -> WorkflowRunListWidget {
+        WorkflowRunListWidget {
+            github_service: Arc::new(github_service),
+            sources: repos
+                .iter()
+                .cloned()
+                .map(RepositorySource::Repository)
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    fn run_ids(widget: &WorkflowRunListWidget) -> Vec<u64> {
+        let state = widget.state.read_or_recover();
+        state.workflow_runs.iter().map(|r| r.id.0).collect()
+    }
+
+    fn selected(widget: &WorkflowRunListWidget) -> (Option<u64>, Option<usize>, usize) {
+        let state = widget.state.read_or_recover();
+        (
+            state.selected_run.map(|id| id.0),
+            state.table_state.selected(),
+            state.table_state.offset(),
+        )
+    }
+
+    /// Discover the repositories and fetch their runs, like the scheduler.
+    async fn refresh(widget: &mut WorkflowRunListWidget) {
+        let mut pending = JoinSet::new();
+        let mut discovery = JoinSet::new();
+
+        widget.refresh_all();
+        widget.discover_repos(&mut discovery);
+        while let Some(repos) = discovery.join_next().await {
+            widget.on_discovery(repos.unwrap());
+        }
+
+        widget.refresh_due(&mut pending, &mut discovery);
+        while let Some(res) = pending.join_next().await {
+            let (repo, runs) = res.unwrap();
+            widget.on_repo_runs(repo, runs);
+        }
+    }
+
+    #[test]
+    fn selection_stays_on_the_same_run_and_row() {
+        let a = repo("octo", "a");
+        let mut state = WorkflowListState {
+            workflow_runs: vec![run(3, &a), run(2, &a), run(1, &a)],
+            ..Default::default()
+        };
+        state.select(Some(1));
+        *state.table_state.offset_mut() = 1;
+
+        // A new run pushes the selected run down, the view scrolls with it.
+        state.workflow_runs.insert(0, run(4, &a));
+        state.restore_selection();
+        assert_eq!(state.selected_run, Some(RunId(2)));
+        assert_eq!(state.table_state.selected(), Some(2));
+        assert_eq!(state.table_state.offset(), 2);
+
+        // The run that takes the place of a removed run is selected.
+        state.workflow_runs.remove(2);
+        state.restore_selection();
+        assert_eq!(state.selected_run, Some(RunId(1)));
+        assert_eq!(state.table_state.selected(), Some(2));
+
+        // The first run is selected when there was no selection.
+        state.table_state.select(None);
+        state.restore_selection();
+        assert_eq!(state.selected_run, Some(RunId(4)));
+        assert_eq!(state.table_state.selected(), Some(0));
+    }
+
+    #[tokio::test]
+    async fn refreshed_runs_are_merged_by_id() {
+        let a = repo("octo", "a");
+        let b = repo("octo", "b");
+
+        let mut github_service = MockGitHubService::new();
+        let repos = vec![a.clone(), b.clone()];
+        github_service
+            .expect_discover_repos()
+            .returning(move |_| Ok(repos.clone()));
+        let mut seq = mockall::Sequence::new();
+        let (first, second) = (vec![run(3, &a), run(1, &a)], vec![run(4, &a), run(3, &a)]);
+        github_service
+            .expect_list_runs()
+            .withf(|r| r.name == "a")
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(move |_| Ok(first.clone()));
+        github_service
+            .expect_list_runs()
+            .withf(|r| r.name == "a")
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(move |_| Ok(second.clone()));
+        let runs_b = vec![run(2, &b)];
+        github_service
+            .expect_list_runs()
+            .withf(|r| r.name == "b")
+            .times(2)
+            .returning(move |_| Ok(runs_b.clone()));
+
+        let mut widget = widget(github_service, &[a.clone(), b.clone()]);
+
+        refresh(&mut widget).await;
+        assert_eq!(run_ids(&widget), [3, 2, 1]);
+        widget.state.write_or_recover().select(Some(1));
+
+        // The run 1 is gone, the run 4 is new, the runs of `b` are kept.
+        refresh(&mut widget).await;
+        assert_eq!(run_ids(&widget), [4, 3, 2]);
+        assert_eq!(selected(&widget), (Some(2), Some(2), 1));
+    }
+
+    #[tokio::test]
+    async fn runs_of_removed_repos_are_forgotten() {
+        let a = repo("octo", "a");
+        let b = repo("octo", "B");
+
+        let mut github_service = MockGitHubService::new();
+        let mut seq = mockall::Sequence::new();
+        let repos = vec![a.clone(), b.clone()];
+        github_service
+            .expect_discover_repos()
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(move |_| Ok(repos.clone()));
+        let repos = vec![a.clone()];
+        github_service
+            .expect_discover_repos()
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(move |_| Ok(repos.clone()));
+        github_service.expect_list_runs().returning(|r| {
+            let id = if r.name == "a" { 1 } else { 2 };
+            Ok(vec![run(id, r)])
+        });
+
+        let mut widget = widget(github_service, &[a.clone(), b.clone()]);
+
+        refresh(&mut widget).await;
+        assert_eq!(run_ids(&widget), [2, 1]);
+        {
+            let mut state = widget.state.write_or_recover();
+            state.select(Some(0));
+            *state.table_state.offset_mut() = 0;
+        }
+
+        refresh(&mut widget).await;
+        assert_eq!(run_ids(&widget), [1]);
+        assert_eq!(selected(&widget), (Some(1), Some(0), 0));
+        assert!(!widget.repo_refreshes.contains_key(&repo_key("octo", "b")));
+        assert!(widget.repo_refreshes.contains_key(&repo_key("octo", "a")));
+    }
+
+    #[test]
+    fn runs_of_a_repo_removed_while_loading_are_dropped() {
+        let a = repo("octo", "a");
+        let b = repo("octo", "b");
+
+        let mut widget = widget(MockGitHubService::new(), std::slice::from_ref(&a));
+        widget.repos = vec![a.clone()];
+        widget.discovered = true;
+
+        widget.on_repo_runs(b.clone(), Ok(vec![run(1, &b)]));
+        assert_eq!(run_ids(&widget), Vec::<u64>::new());
     }
 }